@@ -0,0 +1,204 @@
+//! Listing the access wideners that mods declare
+
+use crate::{
+	color, csv_list, csv_row, diff, display_name, is_csv, lint, loader_mod_ids, sort_fields, JarCommand, RunContext, SortOptions,
+	TraversedJar, EXIT_LINT_ERRORS, EXIT_NO_MATCHES,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	process,
+};
+
+/// Prints access widener files in mods in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct AccessWidenerCommand {
+	/// Filter the files using this search string
+	#[clap(long)]
+	filter: Option<String>,
+	/// Check the access wideners for syntax errors and settings that don't work in production, instead of printing them
+	#[clap(long, conflicts_with = "format")]
+	pub lint: bool,
+	/// Print the output in this format instead of a human-readable listing
+	#[clap(long, possible_values = &["text", "csv"])]
+	pub format: Option<String>,
+	#[clap(flatten)]
+	sort: SortOptions,
+}
+
+impl JarCommand for AccessWidenerCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn is_scripted(&self) -> bool {
+		is_csv(&self.format)
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		struct FabricJar {
+			file_names: BTreeSet<String>,
+			access_wideners: BTreeSet<String>,
+			/// Forge access transformers, from jars that support Forge
+			access_transformers: BTreeSet<String>,
+		}
+
+		let mut collated_jars: BTreeMap<String, FabricJar> = BTreeMap::new();
+
+		fn collate_contents(dest: &mut BTreeSet<String>, contents: Option<String>, filter: Option<&str>) {
+			if let Some(contents) = contents {
+				if filter.is_none_or(|filter| contents.to_lowercase().contains(filter)) {
+					dest.insert(contents);
+				}
+			}
+		}
+
+		fn recursively_collate(
+			dest: &mut BTreeMap<String, FabricJar>, jar: TraversedJar, file_name: &str, filter: Option<String>,
+		) {
+			let (mod_id, contained_jars, access_widener_contents, access_transformer_contents) = match jar {
+				TraversedJar::FabricJar {
+					mod_id,
+					contained_jars,
+					access_widener_contents,
+					access_transformer_contents,
+					..
+				} => (mod_id, contained_jars, access_widener_contents, access_transformer_contents),
+				TraversedJar::OtherLoaderJar {
+					mods,
+					access_transformer_contents,
+					..
+				} => (loader_mod_ids(&mods), BTreeMap::new(), None, access_transformer_contents),
+				TraversedJar::NonMod => return,
+			};
+
+			let collate_dest = dest.entry(mod_id).or_insert(FabricJar {
+				file_names: BTreeSet::new(),
+				access_wideners: BTreeSet::new(),
+				access_transformers: BTreeSet::new(),
+			});
+
+			collate_dest.file_names.insert(file_name.to_owned());
+			collate_contents(&mut collate_dest.access_wideners, access_widener_contents, filter.as_deref());
+			collate_contents(
+				&mut collate_dest.access_transformers,
+				access_transformer_contents,
+				filter.as_deref(),
+			);
+
+			for contained_jar in contained_jars {
+				recursively_collate(dest, contained_jar.1, contained_jar.0.as_str(), filter.to_owned());
+			}
+		}
+
+		let filter = self.filter.as_ref();
+		let fields = sort_fields(&processed_jars);
+		for jar in processed_jars {
+			recursively_collate(
+				&mut collated_jars,
+				jar.1,
+				&display_name(&jar.0),
+				filter.map(|filter| filter.as_str().to_lowercase()),
+			);
+		}
+		let mut collated_jars = self.sort.sorted(collated_jars, &fields);
+
+		if is_csv(&self.format) {
+			println!(
+				"{}",
+				csv_row(&["mod_id", "files", "access", "type", "class", "name", "descriptor", "source"])
+			);
+			for (mod_id, jar) in &collated_jars {
+				let files = csv_list(&jar.file_names);
+				for aw in &jar.access_wideners {
+					for entry in diff::access_widener_entries(aw) {
+						let mut fields: Vec<&str> = entry.split(' ').collect();
+						fields.resize(5, "");
+						println!("{}", csv_row(&[&[mod_id.as_str(), &files][..], &fields, &["aw"]].concat()));
+					}
+				}
+				for at in &jar.access_transformers {
+					for entry in diff::access_transformer_entries(at) {
+						println!("{}", csv_row(&[&[mod_id.as_str(), &files][..], &entry[..], &["at"]].concat()));
+					}
+				}
+			}
+			return Ok(());
+		}
+
+		collated_jars.retain(|(_, jar)| !jar.access_wideners.is_empty() || !jar.access_transformers.is_empty());
+
+		if self.lint {
+			let mut errors = 0;
+			let mut warnings = 0;
+			for (mod_id, jar) in &collated_jars {
+				let problems: Vec<lint::Problem> = jar
+					.access_wideners
+					.iter()
+					.flat_map(|aw| lint::lint_access_widener(aw))
+					.collect();
+				if problems.is_empty() {
+					continue;
+				}
+				println!(
+					"{} ({})",
+					color::bold(mod_id),
+					jar.file_names.iter().cloned().collect::<Vec<String>>().join(", ")
+				);
+				let (jar_errors, jar_warnings) = lint::print_problems(&problems);
+				errors += jar_errors;
+				warnings += jar_warnings;
+			}
+			if errors == 0 && warnings == 0 {
+				println!("No problems found");
+			} else {
+				println!("{} errors, {} warnings", errors, warnings);
+				if errors > 0 {
+					process::exit(EXIT_LINT_ERRORS);
+				}
+			}
+			return Ok(());
+		}
+		let id_width = collated_jars
+			.iter()
+			.map(|(id, _)| id.chars().count())
+			.max()
+			.unwrap_or_default();
+
+		let mut matched_jars = false;
+		for jar in &collated_jars {
+			matched_jars = true;
+			println!(
+				"{} ({})",
+				color::padded(&jar.0, id_width, color::bold),
+				jar.1.file_names.iter().cloned().collect::<Vec<String>>().join(", ")
+			);
+			for aw in jar.1.access_wideners.iter() {
+				for line in aw.lines() {
+					println!("    {}", line);
+				}
+			}
+			if !jar.1.access_transformers.is_empty() {
+				println!("Access transformer (Forge):");
+				for at in jar.1.access_transformers.iter() {
+					for line in at.lines() {
+						println!("    {}", line);
+					}
+				}
+			}
+		}
+		if !matched_jars {
+			if self.filter.is_some() {
+				println!("No jars that match the given filter found!");
+				process::exit(EXIT_NO_MATCHES);
+			} else {
+				println!("No jars with AWs or ATs found!");
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,124 @@
+use crate::{
+	environment_report, file_name, metadata_files, read_mod_json, top_level_providers, walk_archives, walk_jars, JarCommand,
+	RunContext, ScanOptions, TraversedJar,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use serde_json::json;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs::File,
+	io::{Read, Seek, Write},
+	path::{Path, PathBuf},
+};
+use zip::{write::FileOptions, ZipWriter};
+
+/// Lists mod ids that are supplied by more than one top-level jar, or that are present in more than one version
+fn conflicts(jars: &[(PathBuf, TraversedJar)]) -> Vec<String> {
+	let mut versions: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+	for jar in jars {
+		walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, _, _| {
+			if let TraversedJar::FabricJar { mod_id, mod_version, .. } = nested {
+				versions.entry(mod_id).or_default().insert(mod_version);
+			}
+		});
+	}
+
+	let mut lines = vec![];
+	for (mod_id, files) in top_level_providers(jars).iter().filter(|(_, files)| files.len() > 1) {
+		lines.push(format!(
+			"{} is supplied by multiple jars: {}",
+			mod_id,
+			files.iter().cloned().collect::<Vec<_>>().join(", ")
+		));
+	}
+	for (mod_id, versions) in versions.iter().filter(|(_, versions)| versions.len() > 1) {
+		lines.push(format!(
+			"{} is present in multiple versions: {}",
+			mod_id,
+			versions.iter().cloned().collect::<Vec<_>>().join(", ")
+		));
+	}
+	lines
+}
+
+fn write_lines<W: Write + Seek>(writer: &mut ZipWriter<W>, name: &str, lines: &[String], empty_message: &str) -> Result<()> {
+	writer.start_file(name, FileOptions::default())?;
+	if lines.is_empty() {
+		writeln!(writer, "{}", empty_message)?;
+	}
+	for line in lines {
+		writeln!(writer, "{}", line)?;
+	}
+	Ok(())
+}
+
+/// Writes the inventory, reports and the metadata files of every mod in `jars` to a zip file at `out`
+pub fn write_bundle(out: &Path, jars: &[(PathBuf, TraversedJar)], scan: &ScanOptions) -> Result<()> {
+	let mut writer = ZipWriter::new(File::create(out)?);
+
+	writer.start_file("inventory.json", FileOptions::default())?;
+	let inventory: Vec<_> = jars
+		.iter()
+		.map(|jar| json!({ "file": file_name(&jar.0), "jar": jar.1 }))
+		.collect();
+	serde_json::to_writer_pretty(&mut writer, &inventory)?;
+
+	write_lines(&mut writer, "conflicts.txt", &conflicts(jars), "No conflicts found")?;
+	write_lines(
+		&mut writer,
+		"environments.txt",
+		&environment_report(jars, None).0,
+		"No valid jars found",
+	)?;
+
+	for jar in jars {
+		let mut path = vec![file_name(&jar.0).to_owned()];
+		walk_archives(&jar.0, &mut path, scan, &mut |path, zip| {
+			let fabric_mod_json = match read_mod_json(zip) {
+				Ok(fabric_mod_json) => fabric_mod_json,
+				Err(_) => return Ok(()),
+			};
+
+			for metadata_file in metadata_files(zip, &fabric_mod_json) {
+				let mut contents = vec![];
+				if let Ok(mut file) = zip.by_name(&metadata_file) {
+					file.read_to_end(&mut contents)?;
+				} else {
+					continue;
+				}
+				writer.start_file(
+					format!("metadata/{}/{}", path.join("/"), metadata_file),
+					FileOptions::default(),
+				)?;
+				writer.write_all(&contents)?;
+			}
+			Ok(())
+		})?;
+	}
+
+	writer.finish()?;
+	Ok(())
+}
+
+/// Packages an inventory, conflict and environment reports and all metadata files into a zip for support requests
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct BundleReportCommand {
+	/// The zip file to write the report to
+	out: PathBuf,
+}
+
+impl JarCommand for BundleReportCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("bundle-report")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		write_bundle(&self.out, &processed_jars, scan)?;
+		println!("Wrote report for {} jars to {}", processed_jars.len(), self.out.display());
+
+		Ok(())
+	}
+}
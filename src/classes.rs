@@ -0,0 +1,141 @@
+//! Searching the classes of mods
+
+use crate::{color, walk_labelled_archives, DetailLevel, JarCommand, RunContext, TraversedJar, EXIT_UNREADABLE_JARS};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{collections::BTreeMap, path::PathBuf, process};
+
+/// Lists the packages in each mod in the current folder with the number of classes in them
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct ClassesCommand {
+	/// Number of package name segments to group classes by
+	#[clap(long, default_value = "3")]
+	depth: usize,
+	/// Show every package as a tree instead of grouping them
+	#[clap(long, conflicts_with = "depth")]
+	tree: bool,
+}
+
+impl JarCommand for ClassesCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("classes")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		#[derive(Default)]
+		struct Package {
+			/// Number of classes directly in this package
+			classes: usize,
+			subpackages: BTreeMap<String, Package>,
+		}
+
+		impl Package {
+			fn total(&self) -> usize {
+				self.classes + self.subpackages.values().map(Package::total).sum::<usize>()
+			}
+
+			fn print(&self, name: &str, padding: usize) {
+				// Join packages that only contain a single subpackage, e.g. net > fabricmc > api to net.fabricmc.api
+				let mut name = name.to_owned();
+				let mut package = self;
+				while package.classes == 0 && package.subpackages.len() == 1 {
+					let (subpackage_name, subpackage) = package.subpackages.iter().next().unwrap();
+					name = format!("{}.{}", name, subpackage_name);
+					package = subpackage;
+				}
+				println!("{}{}: {}", "    ".repeat(padding), name, package.total());
+				for (subpackage_name, subpackage) in &package.subpackages {
+					subpackage.print(subpackage_name, padding + 1);
+				}
+			}
+		}
+
+		struct ModClasses {
+			paths: Vec<String>,
+			root: Package,
+		}
+
+		let (walked, unreadable) = walk_labelled_archives(&processed_jars, scan, "classes", |zip| {
+			let mut root = Package::default();
+			for name in zip.file_names() {
+				let class = match name.strip_suffix(".class") {
+					Some(class) if !class.starts_with("META-INF/") => class,
+					_ => continue,
+				};
+				let mut segments: Vec<&str> = class.split('/').collect();
+				segments.pop();
+				let mut package = &mut root;
+				for segment in segments {
+					package = package.subpackages.entry(segment.to_owned()).or_default();
+				}
+				package.classes += 1;
+			}
+			Ok(root)
+		});
+
+		let mut mods: BTreeMap<String, ModClasses> = BTreeMap::new();
+		for (label, path, root) in walked {
+			// Only count the classes of the first copy of each mod
+			mods.entry(label)
+				.or_insert(ModClasses { paths: vec![], root })
+				.paths
+				.push(path);
+		}
+
+		for (label, mod_classes) in &mods {
+			println!(
+				"{} ({}): {} classes",
+				color::bold(label),
+				mod_classes.paths.join(", "),
+				mod_classes.root.total()
+			);
+			if self.tree {
+				if mod_classes.root.classes > 0 {
+					println!("    (default package): {}", mod_classes.root.classes);
+				}
+				for (name, package) in &mod_classes.root.subpackages {
+					package.print(name, 1);
+				}
+				continue;
+			}
+
+			/// Counts the classes of each package, merging the packages under `depth` segments into their parent
+			fn group(package: &Package, name: &str, depth: usize, groups: &mut Vec<(String, usize)>) {
+				if depth == 0 || package.subpackages.is_empty() {
+					groups.push((name.to_owned(), package.total()));
+					return;
+				}
+				if package.classes > 0 {
+					groups.push((name.to_owned(), package.classes));
+				}
+				for (subpackage_name, subpackage) in &package.subpackages {
+					let subpackage_name = if name.is_empty() {
+						subpackage_name.clone()
+					} else {
+						format!("{}.{}", name, subpackage_name)
+					};
+					group(subpackage, &subpackage_name, depth - 1, groups);
+				}
+			}
+
+			let mut groups = vec![];
+			group(&mod_classes.root, "", self.depth, &mut groups);
+			groups.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+			for (name, classes) in groups {
+				let name = if name.is_empty() { "(default package)" } else { &name };
+				println!("    {}: {}", name, classes);
+			}
+		}
+		if unreadable {
+			process::exit(EXIT_UNREADABLE_JARS);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,301 @@
+//! Just enough of the class file format to read the constant pool and annotations
+
+use anyhow::Result;
+
+enum Constant {
+	Utf8(String),
+	Class(u16),
+	String(u16),
+	Other,
+}
+
+/// The constants of a class file, which hold every string literal and the names of every referenced class, field and
+/// method
+pub struct ConstantPool {
+	/// Indexed by constant pool index; index 0 and the second slot of longs and doubles are `Other`
+	entries: Vec<Constant>,
+}
+
+struct Reader<'a> {
+	bytes: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+		let end = self.pos + len;
+		if end > self.bytes.len() {
+			anyhow::bail!("class file is truncated");
+		}
+		let taken = &self.bytes[self.pos..end];
+		self.pos = end;
+		Ok(taken)
+	}
+
+	fn u8(&mut self) -> Result<u8> {
+		Ok(self.take(1)?[0])
+	}
+
+	fn u16(&mut self) -> Result<u16> {
+		let bytes = self.take(2)?;
+		Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+	}
+
+	fn u32(&mut self) -> Result<u32> {
+		let bytes = self.take(4)?;
+		Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+	}
+}
+
+impl ConstantPool {
+	pub fn parse(class: &[u8]) -> Result<ConstantPool> {
+		ConstantPool::read(&mut Reader { bytes: class, pos: 0 })
+	}
+
+	/// Reads the header and constant pool, leaving `reader` at the access flags of the class
+	fn read(reader: &mut Reader) -> Result<ConstantPool> {
+		if reader.take(4)? != [0xCA, 0xFE, 0xBA, 0xBE] {
+			anyhow::bail!("not a class file");
+		}
+		reader.take(4)?;
+
+		let count = reader.u16()? as usize;
+		let mut entries = Vec::with_capacity(count);
+		entries.push(Constant::Other);
+		while entries.len() < count {
+			let constant = match reader.u8()? {
+				1 => {
+					let len = reader.u16()? as usize;
+					// Modified UTF-8 only differs from UTF-8 for nulls and supplementary characters
+					Constant::Utf8(String::from_utf8_lossy(reader.take(len)?).into_owned())
+				}
+				7 => Constant::Class(reader.u16()?),
+				3 | 4 => {
+					reader.take(4)?;
+					Constant::Other
+				}
+				5 | 6 => {
+					reader.take(8)?;
+					entries.push(Constant::Other);
+					Constant::Other
+				}
+				8 => Constant::String(reader.u16()?),
+				16 | 19 | 20 => {
+					reader.take(2)?;
+					Constant::Other
+				}
+				9 | 10 | 11 | 12 | 17 | 18 => {
+					reader.take(4)?;
+					Constant::Other
+				}
+				15 => {
+					reader.take(3)?;
+					Constant::Other
+				}
+				tag => anyhow::bail!("unknown constant pool tag {}", tag),
+			};
+			entries.push(constant);
+		}
+		Ok(ConstantPool { entries })
+	}
+
+	fn utf8_at(&self, index: u16) -> Option<&str> {
+		match self.entries.get(index as usize) {
+			Some(Constant::Utf8(value)) => Some(value),
+			_ => None,
+		}
+	}
+
+	fn string_at(&self, index: u16) -> Result<String> {
+		match self.utf8_at(index) {
+			Some(value) => Ok(value.to_owned()),
+			None => anyhow::bail!("constant pool index {} isn't a UTF-8 constant", index),
+		}
+	}
+
+	fn class_at(&self, index: u16) -> Result<String> {
+		match self.entries.get(index as usize) {
+			Some(Constant::Class(name)) => self.string_at(*name),
+			_ => anyhow::bail!("constant pool index {} isn't a class", index),
+		}
+	}
+
+	/// Every UTF-8 constant, including names and descriptors as well as string literals
+	pub fn utf8(&self) -> impl Iterator<Item = &str> {
+		self.entries.iter().filter_map(|entry| match entry {
+			Constant::Utf8(value) => Some(value.as_str()),
+			_ => None,
+		})
+	}
+
+	/// The string literals used by the class
+	pub fn strings(&self) -> impl Iterator<Item = &str> {
+		self.entries.iter().filter_map(move |entry| match entry {
+			Constant::String(index) => self.utf8_at(*index),
+			_ => None,
+		})
+	}
+
+	/// The internal names (e.g. `java/lang/String`) of the classes referenced by the class
+	pub fn classes(&self) -> impl Iterator<Item = &str> {
+		self.entries.iter().filter_map(move |entry| match entry {
+			Constant::Class(index) => self.utf8_at(*index),
+			_ => None,
+		})
+	}
+}
+
+/// The value of an annotation element
+pub enum ElementValue {
+	String(String),
+	/// A class literal, as a descriptor such as `Lnet/minecraft/class_1297;`
+	Class(String),
+	Array(Vec<ElementValue>),
+	/// A number, character, boolean, enum constant or nested annotation
+	Other,
+}
+
+impl ElementValue {
+	/// The strings in this value or array of values
+	pub fn strings(&self) -> Vec<&str> {
+		match self {
+			ElementValue::String(value) => vec![value],
+			ElementValue::Array(values) => values.iter().flat_map(ElementValue::strings).collect(),
+			_ => vec![],
+		}
+	}
+
+	/// The internal names of the classes in this value or array of values
+	pub fn classes(&self) -> Vec<&str> {
+		match self {
+			ElementValue::Class(descriptor) => vec![descriptor.trim_start_matches('L').trim_end_matches(';')],
+			ElementValue::Array(values) => values.iter().flat_map(ElementValue::classes).collect(),
+			_ => vec![],
+		}
+	}
+}
+
+/// An annotation on a class or method, such as `@Inject(method = "tick", at = @At("HEAD"))`
+pub struct Annotation {
+	/// The descriptor of the annotation class, such as `Lorg/spongepowered/asm/mixin/injection/Inject;`
+	pub descriptor: String,
+	pub elements: Vec<(String, ElementValue)>,
+}
+
+impl Annotation {
+	/// The name of the annotation class without its package, such as `Inject`
+	pub fn simple_name(&self) -> &str {
+		let name = self.descriptor.trim_start_matches('L').trim_end_matches(';');
+		name.rsplit('/').next().unwrap_or(name)
+	}
+
+	pub fn get(&self, name: &str) -> Option<&ElementValue> {
+		self.elements
+			.iter()
+			.find(|(element, _)| element == name)
+			.map(|(_, value)| value)
+	}
+}
+
+pub struct Method {
+	pub name: String,
+	pub annotations: Vec<Annotation>,
+}
+
+/// The interfaces and annotations of a class. Annotations of both retentions are read, since Mixin's annotations are
+/// only kept in the class file.
+pub struct ClassFile {
+	/// Internal names, such as `net/minecraft/class_1297`
+	pub interfaces: Vec<String>,
+	pub annotations: Vec<Annotation>,
+	pub methods: Vec<Method>,
+}
+
+fn read_element_value(reader: &mut Reader, pool: &ConstantPool) -> Result<ElementValue> {
+	Ok(match reader.u8()? {
+		b's' => ElementValue::String(pool.string_at(reader.u16()?)?),
+		b'c' => ElementValue::Class(pool.string_at(reader.u16()?)?),
+		b'e' => {
+			reader.take(4)?;
+			ElementValue::Other
+		}
+		b'@' => {
+			read_annotation(reader, pool)?;
+			ElementValue::Other
+		}
+		b'[' => {
+			let count = reader.u16()?;
+			let mut values = Vec::with_capacity(count as usize);
+			for _ in 0..count {
+				values.push(read_element_value(reader, pool)?);
+			}
+			ElementValue::Array(values)
+		}
+		b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' => {
+			reader.u16()?;
+			ElementValue::Other
+		}
+		tag => anyhow::bail!("unknown annotation element tag {}", tag),
+	})
+}
+
+fn read_annotation(reader: &mut Reader, pool: &ConstantPool) -> Result<Annotation> {
+	let descriptor = pool.string_at(reader.u16()?)?;
+	let count = reader.u16()?;
+	let mut elements = Vec::with_capacity(count as usize);
+	for _ in 0..count {
+		let name = pool.string_at(reader.u16()?)?;
+		elements.push((name, read_element_value(reader, pool)?));
+	}
+	Ok(Annotation { descriptor, elements })
+}
+
+/// Reads a list of attributes, returning the annotations in them
+fn read_attributes(reader: &mut Reader, pool: &ConstantPool) -> Result<Vec<Annotation>> {
+	let mut annotations = vec![];
+	for _ in 0..reader.u16()? {
+		let name = pool.string_at(reader.u16()?)?;
+		let len = reader.u32()? as usize;
+		let contents = reader.take(len)?;
+		if name == "RuntimeVisibleAnnotations" || name == "RuntimeInvisibleAnnotations" {
+			let mut attribute = Reader { bytes: contents, pos: 0 };
+			for _ in 0..attribute.u16()? {
+				annotations.push(read_annotation(&mut attribute, pool)?);
+			}
+		}
+	}
+	Ok(annotations)
+}
+
+impl ClassFile {
+	pub fn parse(class: &[u8]) -> Result<ClassFile> {
+		let mut reader = Reader { bytes: class, pos: 0 };
+		let pool = ConstantPool::read(&mut reader)?;
+		// Access flags, this class and superclass
+		reader.take(6)?;
+		let mut interfaces = vec![];
+		for _ in 0..reader.u16()? {
+			interfaces.push(pool.class_at(reader.u16()?)?);
+		}
+		for _ in 0..reader.u16()? {
+			reader.take(6)?;
+			read_attributes(&mut reader, &pool)?;
+		}
+		let mut methods = vec![];
+		for _ in 0..reader.u16()? {
+			reader.u16()?;
+			let name = pool.string_at(reader.u16()?)?;
+			reader.u16()?;
+			methods.push(Method {
+				name,
+				annotations: read_attributes(&mut reader, &pool)?,
+			});
+		}
+		let annotations = read_attributes(&mut reader, &pool)?;
+		Ok(ClassFile {
+			interfaces,
+			annotations,
+			methods,
+		})
+	}
+}
@@ -0,0 +1,50 @@
+use std::{
+	env,
+	io::{self, IsTerminal},
+	sync::atomic::{AtomicBool, Ordering},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns colored output on or off from a `--color` value; `auto` colors output when stdout is a terminal and the
+/// NO_COLOR environment variable isn't set. Only stdout is colored, so messages on stderr are printed without color.
+pub fn init(choice: &str) {
+	let enabled = match choice {
+		"always" => true,
+		"never" => false,
+		_ => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
+	};
+	ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn paint(code: &str, text: &str) -> String {
+	if ENABLED.load(Ordering::Relaxed) {
+		format!("\x1b[{}m{}\x1b[0m", code, text)
+	} else {
+		text.to_owned()
+	}
+}
+
+pub fn bold(text: &str) -> String {
+	paint("1", text)
+}
+
+/// Used for problems, such as conflicts and missing dependencies
+pub fn red(text: &str) -> String {
+	paint("31", text)
+}
+
+/// Used for client-only sections
+pub fn cyan(text: &str) -> String {
+	paint("36", text)
+}
+
+/// Used for server-only sections
+pub fn yellow(text: &str) -> String {
+	paint("33", text)
+}
+
+/// Applies `style` to `text` and pads it to `width` columns, so that the following column lines up regardless of color
+pub fn padded(text: &str, width: usize, style: fn(&str) -> String) -> String {
+	format!("{}{}", style(text), " ".repeat(width.saturating_sub(text.chars().count())))
+}
@@ -0,0 +1,120 @@
+use crate::{lookup, Opts, SubCommand};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{
+	env, fs,
+	path::{Path, PathBuf},
+};
+
+const FILE_NAME: &str = "mod_jar_inspector.toml";
+
+/// Defaults read from `mod_jar_inspector.toml`, which are used for options that aren't given on the command line
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+	/// Folder to read mods from, relative to the folder containing the config file
+	dir: Option<PathBuf>,
+	/// Glob patterns of jar file names to read, unless patterns are given on the command line
+	#[serde(default)]
+	include: Vec<String>,
+	/// Glob patterns of jar file names to skip, in addition to those given on the command line
+	#[serde(default)]
+	exclude: Vec<String>,
+	color: Option<String>,
+	/// Output format for the commands that support `--format`
+	format: Option<String>,
+	threads: Option<usize>,
+	curseforge_api_key: Option<String>,
+}
+
+/// The folder for user configuration files: `$XDG_CONFIG_HOME`, falling back to `~/.config` (or `%APPDATA%` on
+/// Windows)
+fn config_dir() -> Option<PathBuf> {
+	env::var_os("XDG_CONFIG_HOME")
+		.filter(|dir| !dir.is_empty())
+		.map(PathBuf::from)
+		.or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+		.or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+}
+
+/// Reads the config file from the current folder, or from the user's config folder if there isn't one there
+pub fn load() -> Result<Config> {
+	let candidates = std::iter::once(PathBuf::from(".")).chain(config_dir());
+	for dir in candidates {
+		let path = dir.join(FILE_NAME);
+		if !path.is_file() {
+			continue;
+		}
+		let contents = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+		let mut config: Config = toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+		if dir != Path::new(".") {
+			config.dir = config.dir.map(|mods_dir| dir.join(mods_dir));
+		}
+		config
+			.check()
+			.with_context(|| format!("Invalid config in {}", path.display()))?;
+		return Ok(config);
+	}
+	Ok(Config::default())
+}
+
+impl Config {
+	fn check(&self) -> Result<()> {
+		if let Some(color) = &self.color {
+			if !["auto", "always", "never"].contains(&color.as_str()) {
+				anyhow::bail!("color must be auto, always or never, not {}", color);
+			}
+		}
+		if let Some(format) = &self.format {
+			if !["text", "csv"].contains(&format.as_str()) {
+				anyhow::bail!("format must be text or csv, not {}", format);
+			}
+		}
+		for pattern in self.include.iter().chain(&self.exclude) {
+			glob::Pattern::new(pattern).with_context(|| format!("{} is not a valid glob pattern", pattern))?;
+		}
+		Ok(())
+	}
+
+	/// Fills in the options that weren't given on the command line
+	pub fn apply(self, opts: &mut Opts) {
+		if opts.dir.is_none() {
+			opts.dir = self.dir;
+		}
+		if opts.color.is_none() {
+			opts.color = self.color;
+		}
+		if opts.scan.threads.is_none() {
+			opts.scan.threads = self.threads;
+		}
+		if opts.scan.include.is_empty() {
+			opts.scan.include = self
+				.include
+				.iter()
+				.filter_map(|pattern| glob::Pattern::new(pattern).ok())
+				.collect();
+		}
+		opts.scan
+			.exclude
+			.extend(self.exclude.iter().filter_map(|pattern| glob::Pattern::new(pattern).ok()));
+
+		if self.format.is_some() {
+			let format = match &mut opts.subcmd {
+				SubCommand::Mixin(mixin_cmd) => Some(&mut mixin_cmd.format),
+				SubCommand::AccessWidener(aw_cmd) if !aw_cmd.lint => Some(&mut aw_cmd.format),
+				SubCommand::Deps(deps_cmd) if deps_cmd.graph.is_none() => Some(&mut deps_cmd.format),
+				SubCommand::List(list_cmd) if !list_cmd.ids_only && !list_cmd.markdown => Some(&mut list_cmd.format),
+				_ => None,
+			};
+			if let Some(format @ None) = format {
+				*format = self.format;
+			}
+		}
+
+		if let Some(api_key) = self.curseforge_api_key {
+			if env::var_os(lookup::CURSEFORGE_API_KEY_VAR).is_none() {
+				env::set_var(lookup::CURSEFORGE_API_KEY_VAR, api_key);
+			}
+		}
+	}
+}
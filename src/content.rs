@@ -0,0 +1,123 @@
+//! Finding the blocks, items and other content that mods add
+
+use crate::{color, walk_labelled_archives, DetailLevel, JarCommand, RunContext, TraversedJar, EXIT_UNREADABLE_JARS};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{collections::BTreeMap, path::PathBuf, process};
+
+/// Counts the resource pack (`assets`) and data pack (`data`) files of each mod in the current folder by type
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct ContentCommand {
+	/// Only list mods that have assets or data files
+	#[clap(long)]
+	only_content: bool,
+}
+
+impl JarCommand for ContentCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("content")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		/// Folders of `assets/<namespace>` with their labels; files in other folders are counted as other assets
+		const ASSET_TYPES: &[(&[&str], &str)] = &[
+			(&["textures"], "textures"),
+			(&["models"], "models"),
+			(&["lang"], "lang files"),
+			(&["sounds"], "sounds"),
+		];
+		/// Folders of `data/<namespace>` with their labels, including the singular names used since 1.21
+		const DATA_TYPES: &[(&[&str], &str)] = &[
+			(&["recipes", "recipe"], "recipes"),
+			(&["loot_tables", "loot_table"], "loot tables"),
+			(&["tags"], "tags"),
+			(&["advancements", "advancement"], "advancements"),
+			(&["worldgen"], "worldgen"),
+		];
+
+		struct ModContent {
+			paths: Vec<String>,
+			/// Number of files of each type in `types`, followed by the number of other files
+			assets: Vec<usize>,
+			data: Vec<usize>,
+		}
+
+		fn describe(counts: &[usize], types: &[(&[&str], &str)]) -> String {
+			let labels = types.iter().map(|(_, label)| *label).chain(std::iter::once("other"));
+			counts
+				.iter()
+				.zip(labels)
+				.filter(|(count, _)| **count > 0)
+				.map(|(count, label)| format!("{} {}", count, label))
+				.collect::<Vec<_>>()
+				.join(", ")
+		}
+
+		let (walked, unreadable) = walk_labelled_archives(&processed_jars, scan, "files", |zip| {
+			let mut content = ModContent {
+				paths: vec![],
+				assets: vec![0; ASSET_TYPES.len() + 1],
+				data: vec![0; DATA_TYPES.len() + 1],
+			};
+			for name in zip.file_names().filter(|name| !name.ends_with('/')) {
+				let mut segments = name.split('/');
+				let (types, counts) = match segments.next() {
+					Some("assets") => (ASSET_TYPES, &mut content.assets),
+					Some("data") => (DATA_TYPES, &mut content.data),
+					_ => continue,
+				};
+				// Skip the namespace; files directly in the namespace folder (such as icons) aren't content
+				let folder = match (segments.next(), segments.next()) {
+					(Some(_), Some(folder)) if segments.next().is_some() => folder,
+					_ => continue,
+				};
+				let index = types
+					.iter()
+					.position(|(folders, _)| folders.contains(&folder))
+					.unwrap_or(types.len());
+				counts[index] += 1;
+			}
+			Ok(content)
+		});
+
+		let mut mods: BTreeMap<String, ModContent> = BTreeMap::new();
+		for (label, path, content) in walked {
+			// Only count the files of the first copy of each mod
+			mods.entry(label).or_insert(content).paths.push(path);
+		}
+
+		let mut found = false;
+		for (label, content) in &mods {
+			let assets = describe(&content.assets, ASSET_TYPES);
+			let data = describe(&content.data, DATA_TYPES);
+			if self.only_content && assets.is_empty() && data.is_empty() {
+				continue;
+			}
+			found = true;
+			println!("{} ({})", color::bold(label), content.paths.join(", "));
+			if assets.is_empty() && data.is_empty() {
+				println!("    No assets or data files");
+			}
+			if !assets.is_empty() {
+				println!("    Assets: {}", assets);
+			}
+			if !data.is_empty() {
+				println!("    Data: {}", data);
+			}
+		}
+		if !found {
+			println!("No mods with assets or data files found!");
+		}
+		if unreadable {
+			process::exit(EXIT_UNREADABLE_JARS);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,206 @@
+//! Checking and graphing the dependencies between mods
+
+use crate::{
+	color, csv_row, dependency_problems, file_name, is_csv, walk_jars, DetailLevel, JarCommand, RunContext, TraversedJar,
+	BUILTIN_MODS, EXIT_DEPENDENCY_PROBLEMS,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	process,
+};
+
+/// Checks that the dependencies of mods in the current folder are present and satisfied
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct CheckCommand {}
+
+impl JarCommand for CheckCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let problems = dependency_problems(&processed_jars);
+		for problem in &problems {
+			println!("{}", color::red(problem));
+		}
+		if problems.is_empty() {
+			println!("No dependency problems found");
+		} else {
+			println!("{}", color::red(&format!("{} problems found", problems.len())));
+			process::exit(EXIT_DEPENDENCY_PROBLEMS);
+		}
+
+		Ok(())
+	}
+}
+
+/// Lists the dependencies, recommendations and incompatibilities declared by mods in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct DepsCommand {
+	/// Print the dependency graph between mods in this format instead
+	#[clap(long, possible_values = &["dot", "mermaid"], conflicts_with = "format")]
+	pub graph: Option<String>,
+	/// Print the output in this format instead of a human-readable listing
+	#[clap(long, possible_values = &["text", "csv"])]
+	pub format: Option<String>,
+}
+
+impl JarCommand for DepsCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn is_scripted(&self) -> bool {
+		self.graph.is_some() || is_csv(&self.format)
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		type Declared<'a> = [(&'a str, &'a BTreeMap<String, Vec<String>>); 3];
+
+		let mut declared: BTreeMap<(&str, &str), (BTreeSet<&str>, Declared)> = BTreeMap::new();
+		// Ids of mods in the folder, along with the mod providing them if they're only provided by another mod
+		let mut present: BTreeMap<&str, Option<&str>> = BTreeMap::new();
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, _| {
+				if let TraversedJar::FabricJar {
+					mod_id,
+					mod_version,
+					depends,
+					recommends,
+					breaks,
+					provides,
+					..
+				} = nested
+				{
+					present.insert(mod_id, None);
+					for provided in provides {
+						present.entry(provided).or_insert(Some(mod_id));
+					}
+					declared
+						.entry((mod_id, mod_version))
+						.or_insert_with(|| {
+							(
+								BTreeSet::new(),
+								[("Depends", depends), ("Recommends", recommends), ("Breaks", breaks)],
+							)
+						})
+						.0
+						.insert(file_name);
+				}
+			});
+		}
+
+		if is_csv(&self.format) {
+			println!("{}", csv_row(&["mod_id", "version", "files", "kind", "dependency", "ranges"]));
+			for ((mod_id, mod_version), (file_names, kinds)) in &declared {
+				let files = file_names.iter().cloned().collect::<Vec<_>>().join("; ");
+				for (kind, dependencies) in kinds {
+					for (dependency, ranges) in dependencies.iter() {
+						println!(
+							"{}",
+							csv_row(&[
+								mod_id,
+								mod_version,
+								files.as_str(),
+								&kind.to_lowercase(),
+								dependency,
+								&ranges.join(" || ")
+							])
+						);
+					}
+				}
+			}
+			return Ok(());
+		}
+
+		match self.graph.as_deref() {
+			None => {
+				for ((mod_id, mod_version), (file_names, kinds)) in &declared {
+					println!(
+						"{} {} ({})",
+						mod_id,
+						mod_version,
+						file_names.iter().cloned().collect::<Vec<_>>().join(", ")
+					);
+					for (kind, dependencies) in kinds.iter().filter(|(_, dependencies)| !dependencies.is_empty()) {
+						let dependencies: Vec<String> = dependencies
+							.iter()
+							.map(|(id, ranges)| format!("{} {}", id, ranges.join(" || ")))
+							.collect();
+						println!("    {}: {}", kind, dependencies.join(", "));
+					}
+				}
+			}
+			Some(format) => {
+				let mut edges: BTreeSet<(&str, &str, &str)> = BTreeSet::new();
+				let mut nodes: BTreeSet<&str> = BTreeSet::new();
+				for ((mod_id, _), (_, kinds)) in &declared {
+					nodes.insert(mod_id);
+					for (kind, dependencies) in kinds {
+						for dependency in dependencies.keys() {
+							if !BUILTIN_MODS.contains(&dependency.as_str()) {
+								nodes.insert(dependency);
+								edges.insert((mod_id, dependency, kind));
+							}
+						}
+					}
+				}
+				let label = |id: &str| match present.get(id) {
+					Some(None) => id.to_owned(),
+					Some(Some(provider)) => format!("{} (provided by {})", id, provider),
+					None => format!("{} (missing)", id),
+				};
+
+				if format == "dot" {
+					println!("digraph dependencies {{");
+					for node in &nodes {
+						match present.get(node) {
+							Some(None) => println!("    \"{}\";", node),
+							Some(Some(_)) => println!("    \"{}\" [label=\"{}\"];", node, label(node)),
+							None => println!("    \"{}\" [label=\"{}\", style=dashed];", node, label(node)),
+						}
+					}
+					for (from, to, kind) in &edges {
+						let style = match *kind {
+							"Depends" => "",
+							"Recommends" => " [style=dashed]",
+							_ => " [color=red, label=\"breaks\"]",
+						};
+						println!("    \"{}\" -> \"{}\"{};", from, to, style);
+					}
+					println!("}}");
+				} else {
+					// Mermaid node ids can't contain most punctuation, so nodes are numbered and labelled with the mod id
+					let ids: BTreeMap<&str, usize> = nodes.iter().enumerate().map(|(i, node)| (*node, i)).collect();
+					println!("graph LR");
+					for (node, i) in &ids {
+						println!("    n{}[\"{}\"]", i, label(node));
+					}
+					for (from, to, kind) in &edges {
+						let arrow = match *kind {
+							"Depends" => "-->",
+							"Recommends" => "-.->",
+							_ => "--x",
+						};
+						println!("    n{} {} n{}", ids[from], arrow, ids[to]);
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,280 @@
+use crate::{
+	file_name, json, loader_mod_ids, loader_names, read_jar, walk_jars, DetailLevel, Environment, JarContents, ScanOptions,
+	TraversedJar,
+};
+use anyhow::Result;
+use enum_map::EnumMap;
+use serde_json::Value;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	io::Read,
+	path::{Path, PathBuf},
+};
+
+/// Everything a folder contains of a single mod id, merged across every copy of the mod
+#[derive(Default)]
+struct ModSummary {
+	versions: BTreeSet<String>,
+	file_names: BTreeSet<String>,
+	/// Ids of the mods that bundle this mod, empty if it is only a top-level jar
+	bundled_by: BTreeSet<String>,
+	/// Mixins, prefixed with their environment
+	mixins: BTreeSet<String>,
+	access_widener_entries: BTreeSet<String>,
+}
+
+impl ModSummary {
+	fn describe(&self) -> String {
+		let mut description = format!(
+			"{} ({}",
+			self.versions.iter().cloned().collect::<Vec<_>>().join(", "),
+			self.file_names.iter().cloned().collect::<Vec<_>>().join(", ")
+		);
+		if !self.bundled_by.is_empty() {
+			description += &format!(
+				", bundled by {}",
+				self.bundled_by.iter().cloned().collect::<Vec<_>>().join(", ")
+			);
+		}
+		description + ")"
+	}
+}
+
+/// Lists the entries of an access widener, without the header, comments and whitespace differences
+pub fn access_widener_entries(contents: &str) -> impl Iterator<Item = String> + '_ {
+	contents.lines().skip(1).filter_map(|line| {
+		let entry = line.split('#').next().unwrap_or_default();
+		let fields: Vec<&str> = entry.split_whitespace().collect();
+		if fields.is_empty() {
+			None
+		} else {
+			Some(fields.join(" "))
+		}
+	})
+}
+
+/// Splits the entries of a Forge access transformer into their access, type, class, name and descriptor, like the
+/// fields of an access widener entry
+pub fn access_transformer_entries(contents: &str) -> impl Iterator<Item = [&str; 5]> + '_ {
+	contents.lines().filter_map(|line| {
+		let entry = line.split('#').next().unwrap_or_default();
+		match entry.split_whitespace().collect::<Vec<_>>().as_slice() {
+			[access, class] => Some([access, "class", class, "", ""]),
+			[access, class, member] => Some(match member.find('(') {
+				Some(index) => [access, "method", class, &member[..index], &member[index..]],
+				None => [access, "field", class, member, ""],
+			}),
+			_ => None,
+		}
+	})
+}
+
+/// Lists the mixins of a jar, prefixed with their environment
+fn mixin_set(mixins: &EnumMap<Environment, Vec<String>>) -> BTreeSet<String> {
+	mixins
+		.iter()
+		.flat_map(|(environment, env_mixins)| env_mixins.iter().map(move |mixin| format!("{} {}", environment, mixin)))
+		.collect()
+}
+
+fn summarize(jars: &[(PathBuf, TraversedJar)]) -> BTreeMap<&str, ModSummary> {
+	let mut mods: BTreeMap<&str, ModSummary> = BTreeMap::new();
+	for jar in jars {
+		walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, parent| {
+			if let TraversedJar::FabricJar {
+				mod_id,
+				mod_version,
+				mixins,
+				access_widener_contents,
+				..
+			} = nested
+			{
+				let summary = mods.entry(mod_id).or_default();
+				summary.versions.insert(mod_version.clone());
+				summary.file_names.insert(file_name.to_owned());
+				summary.bundled_by.extend(parent.map(str::to_owned));
+				summary.mixins.extend(mixin_set(mixins));
+				if let Some(contents) = access_widener_contents {
+					summary.access_widener_entries.extend(access_widener_entries(contents));
+				}
+			}
+		});
+	}
+	mods
+}
+
+/// Prints the `+`/`-` lines for the entries that were added to or removed from a set
+pub fn print_set_changes(old: &BTreeSet<String>, new: &BTreeSet<String>, indent: &str) {
+	for added in new.difference(old) {
+		println!("{}+ {}", indent, added);
+	}
+	for removed in old.difference(new) {
+		println!("{}- {}", indent, removed);
+	}
+}
+
+/// Prints the mods that were added, removed or changed between two folders, returning whether there were any changes
+pub fn print_folder_diff(old: &[(PathBuf, TraversedJar)], new: &[(PathBuf, TraversedJar)]) -> bool {
+	let old_mods = summarize(old);
+	let new_mods = summarize(new);
+
+	let added: Vec<_> = new_mods.iter().filter(|(id, _)| !old_mods.contains_key(*id)).collect();
+	let removed: Vec<_> = old_mods.iter().filter(|(id, _)| !new_mods.contains_key(*id)).collect();
+	let changed: Vec<_> = old_mods
+		.iter()
+		.filter_map(|(id, old_mod)| new_mods.get(id).map(|new_mod| (id, old_mod, new_mod)))
+		.filter(|(_, old_mod, new_mod)| {
+			old_mod.versions != new_mod.versions
+				|| old_mod.mixins != new_mod.mixins
+				|| old_mod.access_widener_entries != new_mod.access_widener_entries
+		})
+		.collect();
+
+	if !added.is_empty() {
+		println!("Added:");
+		for (id, new_mod) in &added {
+			println!("    {} {}", id, new_mod.describe());
+		}
+	}
+	if !removed.is_empty() {
+		println!("Removed:");
+		for (id, old_mod) in &removed {
+			println!("    {} {}", id, old_mod.describe());
+		}
+	}
+	if !changed.is_empty() {
+		println!("Changed:");
+		for (id, old_mod, new_mod) in &changed {
+			println!("    {} {} -> {}", id, old_mod.describe(), new_mod.describe());
+			if old_mod.mixins != new_mod.mixins {
+				println!("        Mixins: {} -> {}", old_mod.mixins.len(), new_mod.mixins.len());
+			}
+			if old_mod.access_widener_entries != new_mod.access_widener_entries {
+				println!(
+					"        Access widener entries: {} -> {}",
+					old_mod.access_widener_entries.len(),
+					new_mod.access_widener_entries.len()
+				);
+				print_set_changes(
+					&old_mod.access_widener_entries,
+					&new_mod.access_widener_entries,
+					"            ",
+				);
+			}
+		}
+	}
+
+	!(added.is_empty() && removed.is_empty() && changed.is_empty())
+}
+
+/// Prints a heading followed by the `+`/`-` lines for a set, if anything changed
+fn print_section(title: &str, old: &BTreeSet<String>, new: &BTreeSet<String>) -> bool {
+	if old == new {
+		return false;
+	}
+	println!("{}:", title);
+	print_set_changes(old, new, "    ");
+	true
+}
+
+/// Reads the top-level fields of a jar's fabric.mod.json, or nothing if it isn't a Fabric mod
+fn mod_json_fields(path: &Path, scan: &ScanOptions) -> Result<BTreeMap<String, Value>> {
+	let contents = JarContents::open(path, scan)?;
+	let mut zip = contents.archive()?;
+	let mut contents = String::new();
+	match zip.by_name("fabric.mod.json") {
+		Ok(mut file) => file.read_to_string(&mut contents)?,
+		Err(_) => return Ok(BTreeMap::new()),
+	};
+	Ok(json::from_str_lenient(&contents)
+		.map(|(fields, _)| fields)
+		.unwrap_or_default())
+}
+
+fn class_entries(path: &Path, scan: &ScanOptions) -> Result<BTreeSet<String>> {
+	let contents = JarContents::open(path, scan)?;
+	let zip = contents.archive()?;
+	Ok(zip
+		.file_names()
+		.filter(|name| name.ends_with(".class"))
+		.map(str::to_owned)
+		.collect())
+}
+
+/// The details of a jar that are compared between builds
+#[derive(Default)]
+struct JarDetails {
+	mixins: BTreeSet<String>,
+	/// Nested jars, with their mod id and version
+	nested_jars: BTreeSet<String>,
+	access_widener_entries: BTreeSet<String>,
+}
+
+fn jar_details(path: &Path, scan: &ScanOptions) -> Result<JarDetails> {
+	let jar = read_jar(path, DetailLevel::Full, scan).0?;
+	let mut details = JarDetails::default();
+	if let TraversedJar::FabricJar {
+		mixins,
+		contained_jars,
+		access_widener_contents,
+		..
+	} = &jar
+	{
+		details.mixins = mixin_set(mixins);
+		details.nested_jars = contained_jars
+			.iter()
+			.map(|(name, contained_jar)| match contained_jar {
+				TraversedJar::FabricJar { mod_id, mod_version, .. } => format!("{} {} ({})", mod_id, mod_version, name),
+				TraversedJar::OtherLoaderJar { mods, .. } => {
+					format!("{} ({}) [{}]", loader_mod_ids(mods), name, loader_names(mods))
+				}
+				TraversedJar::NonMod => format!("{} (Not a mod)", name),
+			})
+			.collect();
+		details.access_widener_entries = access_widener_contents
+			.as_deref()
+			.map(|contents| access_widener_entries(contents).collect())
+			.unwrap_or_default();
+	}
+	Ok(details)
+}
+
+/// Prints what changed between two builds of a jar, returning whether there were any changes
+pub fn print_jar_diff(old: &Path, new: &Path, scan: &ScanOptions) -> Result<bool> {
+	let mut changed = false;
+
+	let old_fields = mod_json_fields(old, scan)?;
+	let new_fields = mod_json_fields(new, scan)?;
+	let keys: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+	let mut field_lines = vec![];
+	for key in keys {
+		match (old_fields.get(key), new_fields.get(key)) {
+			(Some(old_value), Some(new_value)) if old_value != new_value => {
+				field_lines.push(format!("    ~ {}: {} -> {}", key, old_value, new_value))
+			}
+			(None, Some(new_value)) => field_lines.push(format!("    + {}: {}", key, new_value)),
+			(Some(old_value), None) => field_lines.push(format!("    - {}: {}", key, old_value)),
+			_ => {}
+		}
+	}
+	if !field_lines.is_empty() {
+		changed = true;
+		println!("fabric.mod.json:");
+		for line in field_lines {
+			println!("{}", line);
+		}
+	}
+
+	let old_details = jar_details(old, scan)?;
+	let new_details = jar_details(new, scan)?;
+	changed |= print_section("Mixins", &old_details.mixins, &new_details.mixins);
+	changed |= print_section("Nested jars", &old_details.nested_jars, &new_details.nested_jars);
+	changed |= print_section(
+		"Access widener",
+		&old_details.access_widener_entries,
+		&new_details.access_widener_entries,
+	);
+	changed |= print_section("Classes", &class_entries(old, scan)?, &class_entries(new, scan)?);
+
+	Ok(changed)
+}
@@ -0,0 +1,105 @@
+//! Finding multiple copies of the same mod
+
+use crate::{color, file_name, hash_source, top_level_providers, JarCommand, RunContext, TraversedJar, EXIT_DUPLICATES};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs::File,
+	path::{Path, PathBuf},
+	process,
+};
+
+/// Lists jars in the current folder that are identical, and mod ids that are supplied by more than one top-level jar
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct DuplicatesCommand {
+	/// Delete all but one of each set of identical jars, keeping the one with the shortest name; only prints the jars
+	/// that would be deleted unless --apply is given
+	#[clap(long)]
+	delete_duplicates: bool,
+	/// Actually delete the jars listed by --delete-duplicates
+	#[clap(long, requires = "delete-duplicates")]
+	apply: bool,
+}
+
+impl JarCommand for DuplicatesCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		if self.apply {
+			Some("duplicates --apply")
+		} else {
+			None
+		}
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let mut found = false;
+		let mut deleted: BTreeSet<PathBuf> = BTreeSet::new();
+		// The hash of jars that aren't mods isn't stored when they are read, so they are hashed again here
+		let mut by_hash: BTreeMap<String, Vec<&Path>> = BTreeMap::new();
+		for jar in &processed_jars {
+			let hash = match &jar.1 {
+				TraversedJar::FabricJar { hash, .. } | TraversedJar::OtherLoaderJar { hash, .. } => hash.clone(),
+				TraversedJar::NonMod => hash_source(&mut File::open(&jar.0)?)?,
+			};
+			by_hash.entry(hash).or_default().push(&jar.0);
+		}
+		for paths in by_hash.values_mut().filter(|paths| paths.len() > 1) {
+			// Identical jars that are deleted are no longer a problem
+			found |= !self.apply;
+			// Keep the copy with the shortest name, as copies are usually named like `mod (1).jar`
+			paths.sort_by_key(|path| (file_name(path).len(), file_name(path).to_owned()));
+			let names: Vec<&str> = paths.iter().map(|path| file_name(path)).collect();
+			println!("{}", color::red(&format!("{} are identical", names.join(", "))));
+			if self.delete_duplicates {
+				for path in &paths[1..] {
+					if self.apply {
+						std::fs::remove_file(path)?;
+						println!("    Deleted {}", file_name(path));
+						deleted.insert(path.to_path_buf());
+					} else {
+						println!("    Would delete {}", file_name(path));
+					}
+				}
+			}
+		}
+
+		let remaining: Vec<(PathBuf, TraversedJar)> =
+			processed_jars.into_iter().filter(|jar| !deleted.contains(&jar.0)).collect();
+		for (mod_id, files) in top_level_providers(&remaining).iter().filter(|(_, files)| files.len() > 1) {
+			found = true;
+			let versions: BTreeSet<&str> = remaining
+				.iter()
+				.filter_map(|jar| match &jar.1 {
+					TraversedJar::FabricJar {
+						mod_id: id,
+						mod_version,
+						provides,
+						..
+					} if id == mod_id || provides.iter().any(|provided| provided == mod_id) => Some(mod_version.as_str()),
+					_ => None,
+				})
+				.collect();
+			match versions.iter().next() {
+				Some(version) if versions.len() == 1 => println!(
+					"{}",
+					color::red(&format!(
+						"{} {} is supplied by multiple jars with the same version:",
+						mod_id, version
+					))
+				),
+				_ => println!("{}", color::red(&format!("{} is supplied by multiple jars:", mod_id))),
+			}
+			for file in files {
+				println!("    {}", file);
+			}
+		}
+		if !found {
+			println!("No duplicate mods found");
+		} else {
+			process::exit(EXIT_DUPLICATES);
+		}
+
+		Ok(())
+	}
+}
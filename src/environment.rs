@@ -0,0 +1,50 @@
+//! Listing the environments that mods run in
+
+use crate::{environment_report, DetailLevel, Environment, JarCommand, RunContext, TraversedJar};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::path::PathBuf;
+
+/// Lists the declared environment of mods in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct EnvCommand {
+	/// Flag mods that aren't loaded on a dedicated server
+	#[clap(long, conflicts_with = "client")]
+	server: bool,
+	/// Flag mods that aren't loaded on a client
+	#[clap(long)]
+	client: bool,
+}
+
+impl JarCommand for EnvCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let side = if self.server {
+			Some(Environment::Server)
+		} else if self.client {
+			Some(Environment::Client)
+		} else {
+			None
+		};
+
+		let (lines, flagged) = environment_report(&processed_jars, side);
+		for line in &lines {
+			println!("{}", line);
+		}
+		if lines.is_empty() {
+			println!("No valid jars found!");
+		} else if flagged > 0 {
+			println!("{} mods flagged", flagged);
+		}
+
+		Ok(())
+	}
+}
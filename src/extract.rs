@@ -0,0 +1,141 @@
+use crate::{
+	check_nesting_depth, file_name, hash_source, metadata_files, nested_jar_name, read_mod_json, read_nested_jar, walk_archives,
+	DetailLevel, JarCommand, JarContents, RunContext, TraversalBudget, TraversalLimits, TraversedJar,
+};
+use anyhow::{bail, Result};
+use clap::{AppSettings, Clap};
+use std::{
+	fs,
+	io::{Cursor, Read, Seek},
+	path::{Component, Path, PathBuf},
+	process,
+};
+use zip::ZipArchive;
+
+fn read_entry<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>> {
+	let mut contents = vec![];
+	zip.by_name(name)?.read_to_end(&mut contents)?;
+	Ok(contents)
+}
+
+/// Writes a file from a jar to `dir`, refusing paths that would end up outside of it
+fn write_file(dir: &Path, name: &str, contents: &[u8]) -> Result<()> {
+	if !Path::new(name)
+		.components()
+		.all(|component| matches!(component, Component::Normal(_)))
+	{
+		bail!("Refusing to extract {}, as it is outside of the jar", name);
+	}
+	let path = dir.join(name);
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	fs::write(&path, contents)?;
+	Ok(())
+}
+
+/// Writes the metadata files and nested jars of the mod in `zip` to `dir`, and the metadata of each nested mod to a
+/// folder named after its jar. Returns the number of files written.
+pub fn extract_mod<R: Read + Seek>(zip: &mut ZipArchive<R>, dir: &Path, limits: &TraversalLimits) -> Result<usize> {
+	extract_within(zip, dir, &mut TraversalBudget::new(limits))
+}
+
+fn extract_within<R: Read + Seek>(zip: &mut ZipArchive<R>, dir: &Path, budget: &mut TraversalBudget) -> Result<usize> {
+	let fabric_mod_json = read_mod_json(zip)?;
+	let mut written = 0;
+
+	for metadata_file in metadata_files(zip, &fabric_mod_json) {
+		if let Ok(contents) = read_entry(zip, &metadata_file) {
+			write_file(dir, &metadata_file, &contents)?;
+			written += 1;
+		}
+	}
+
+	for jar_entry in &fabric_mod_json.jars {
+		check_nesting_depth(&jar_entry.file, budget)?;
+		let contents = read_nested_jar(zip, &jar_entry.file, budget)?;
+		write_file(dir, &jar_entry.file, &contents)?;
+		written += 1;
+
+		let hash = hash_source(&mut Cursor::new(&contents[..]))?;
+		if budget.ancestors.contains(&hash) {
+			bail!("a nested jar contains a copy of itself");
+		}
+		let mut nested_zip = ZipArchive::new(Cursor::new(contents))?;
+		if read_mod_json(&mut nested_zip).is_ok() {
+			let nested_name = nested_jar_name(&jar_entry.file);
+			let nested_dir = dir.join(nested_name.strip_suffix(".jar").unwrap_or(&nested_name));
+			budget.ancestors.push(hash);
+			let extracted = extract_within(&mut nested_zip, &nested_dir, budget);
+			budget.ancestors.pop();
+			written += extracted?;
+		}
+	}
+
+	Ok(written)
+}
+
+/// Writes the metadata files and nested jars of a mod in the current folder to a folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct ExtractCommand {
+	/// The id of the mod to extract
+	#[clap(long = "mod")]
+	mod_id: String,
+	/// The folder to write the files to
+	#[clap(long)]
+	out: PathBuf,
+}
+
+impl JarCommand for ExtractCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("extract")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let limits = &context.scan.limits;
+		let scan = context.scan;
+		// Prefer a top-level copy of the mod, falling back to the first nested copy
+		let mut extracted = None;
+		for jar in &processed_jars {
+			if matches!(&jar.1, TraversedJar::FabricJar { mod_id, .. } if *mod_id == self.mod_id) {
+				let written = extract_mod(&mut JarContents::open(&jar.0, scan)?.archive()?, &self.out, limits)?;
+				extracted = Some((file_name(&jar.0).to_owned(), written));
+				break;
+			}
+		}
+		for jar in &processed_jars {
+			if extracted.is_some() {
+				break;
+			}
+			let mut path = vec![file_name(&jar.0).to_owned()];
+			walk_archives(&jar.0, &mut path, scan, &mut |path, zip| {
+				if extracted.is_none() && read_mod_json(zip).is_ok_and(|json| json.id == self.mod_id) {
+					let written = extract_mod(zip, &self.out, limits)?;
+					extracted = Some((path.join(" > "), written));
+				}
+				Ok(())
+			})?;
+		}
+
+		match extracted {
+			Some((source, written)) => println!(
+				"Wrote {} files of {} from {} to {}",
+				written,
+				self.mod_id,
+				source,
+				self.out.display()
+			),
+			None => {
+				println!("No mod with id {} found!", self.mod_id);
+				process::exit(1);
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,125 @@
+//! Listing the Fabric API modules that mods use
+
+use crate::{color, display_name, file_name, version, walk_jars, DetailLevel, JarCommand, RunContext, TraversedJar};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	cmp::Ordering,
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+};
+
+/// Lists the Fabric API modules bundled in mods in the current folder, and flags versions that differ from the ones in
+/// the standalone Fabric API jar
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct FabricApiCommand {}
+
+impl JarCommand for FabricApiCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let quiet = context.quiet;
+		/// Ids of the standalone Fabric API jar, which was renamed from `fabric` to `fabric-api`
+		const FABRIC_API_IDS: &[&str] = &["fabric", "fabric-api"];
+
+		fn is_module(mod_id: &str) -> bool {
+			mod_id.starts_with("fabric-")
+				&& !FABRIC_API_IDS.contains(&mod_id)
+				&& !mod_id.starts_with("fabric-language-")
+				&& mod_id != "fabric-loader"
+		}
+
+		let mut standalone: Option<(&str, &str, BTreeMap<&str, &str>)> = None;
+		// Versions of each module, with the mods that bundle them
+		let mut modules: BTreeMap<&str, BTreeMap<&str, BTreeSet<String>>> = BTreeMap::new();
+		for jar in &processed_jars {
+			if let TraversedJar::FabricJar {
+				mod_id,
+				mod_version,
+				contained_jars,
+				..
+			} = &jar.1
+			{
+				if FABRIC_API_IDS.contains(&mod_id.as_str()) {
+					if standalone.is_some() {
+						if !quiet {
+							eprintln!("Multiple Fabric API jars found, ignoring {}", display_name(&jar.0));
+						}
+						continue;
+					}
+					let versions = contained_jars
+						.values()
+						.filter_map(|nested| match nested {
+							TraversedJar::FabricJar { mod_id, mod_version, .. } if is_module(mod_id) => {
+								Some((mod_id.as_str(), mod_version.as_str()))
+							}
+							_ => None,
+						})
+						.collect();
+					standalone = Some((file_name(&jar.0), mod_version, versions));
+					continue;
+				}
+			}
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, nested_file_name, parent| {
+				if let TraversedJar::FabricJar { mod_id, mod_version, .. } = nested {
+					if is_module(mod_id) {
+						let source = match parent {
+							Some(parent) => format!("{} ({})", parent, file_name(&jar.0)),
+							None => nested_file_name.to_owned(),
+						};
+						modules
+							.entry(mod_id)
+							.or_default()
+							.entry(mod_version)
+							.or_default()
+							.insert(source);
+					}
+				}
+			});
+		}
+
+		match &standalone {
+			Some((file_name, version, _)) => println!("Fabric API {} ({})", version, file_name),
+			None => println!("No standalone Fabric API jar found"),
+		}
+		let mut skewed = 0;
+		for (module, versions) in &modules {
+			println!("{}", color::bold(module));
+			let standalone_version = standalone.as_ref().and_then(|(_, _, versions)| versions.get(module));
+			for (version, sources) in versions {
+				let sources = sources.iter().cloned().collect::<Vec<_>>().join(", ");
+				// Fabric Loader only loads the newest version of each module, whichever jar it comes from
+				let note = match (&standalone, standalone_version) {
+					(None, _) => String::new(),
+					(Some(_), None) => color::yellow(" (not in Fabric API)"),
+					(Some(_), Some(standalone_version)) => match version::compare(version, standalone_version) {
+						Ordering::Equal => String::new(),
+						Ordering::Greater => {
+							skewed += 1;
+							color::red(&format!(" (newer than Fabric API's {}, replaces it)", standalone_version))
+						}
+						Ordering::Less => {
+							skewed += 1;
+							color::yellow(&format!(" (older than Fabric API's {})", standalone_version))
+						}
+					},
+				};
+				println!("    {}: {}{}", version, sources, note);
+			}
+		}
+		if modules.is_empty() {
+			println!("No bundled Fabric API modules found!");
+		} else if skewed > 0 {
+			println!("{} bundled module versions differ from Fabric API", skewed);
+		}
+
+		Ok(())
+	}
+}
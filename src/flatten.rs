@@ -0,0 +1,143 @@
+use crate::{color, file_name, version, DetailLevel, JarCommand, RunContext, TraversedJar};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// A copy of a mod, along with the chain of jar file names leading to it
+#[derive(Clone)]
+pub struct ModCopy<'a> {
+	pub jar: &'a TraversedJar,
+	pub path: Vec<&'a str>,
+}
+
+impl ModCopy<'_> {
+	pub fn version(&self) -> &str {
+		match self.jar {
+			TraversedJar::FabricJar { mod_version, .. } => mod_version,
+			TraversedJar::OtherLoaderJar { .. } | TraversedJar::NonMod => "",
+		}
+	}
+
+	/// Size of the jar file in bytes, including the jars nested in it
+	pub fn size(&self) -> u64 {
+		match self.jar {
+			TraversedJar::FabricJar { size, .. } | TraversedJar::OtherLoaderJar { size, .. } => *size,
+			TraversedJar::NonMod => 0,
+		}
+	}
+
+	pub fn is_nested(&self) -> bool {
+		self.path.len() > 1
+	}
+}
+
+/// The copy of a mod that Fabric Loader loads, and every copy that is available for it to choose from
+pub struct EffectiveMod<'a> {
+	pub selected: ModCopy<'a>,
+	/// Every copy of the mod inside jars that are loaded, including the selected copy
+	pub copies: Vec<ModCopy<'a>>,
+}
+
+/// Computes the mods Fabric Loader would load from the given jars: top-level jars are always loaded, and otherwise the
+/// highest version of each nested mod wins. Mods nested in a copy that isn't loaded aren't available.
+pub fn effective_mods(jars: &[(PathBuf, TraversedJar)]) -> BTreeMap<&str, EffectiveMod<'_>> {
+	let mut effective: BTreeMap<&str, EffectiveMod> = BTreeMap::new();
+	// A nested copy winning can make the mods nested in the copy it replaced unavailable, so repeat until the selection
+	// is stable (with a limit, in case of a cycle)
+	for _ in 0..64 {
+		let mut candidates: BTreeMap<&str, Vec<ModCopy>> = BTreeMap::new();
+		for jar in jars {
+			if let TraversedJar::FabricJar { mod_id, .. } = &jar.1 {
+				candidates.entry(mod_id).or_default().push(ModCopy {
+					jar: &jar.1,
+					path: vec![file_name(&jar.0)],
+				});
+			}
+		}
+		for effective_mod in effective.values() {
+			if let TraversedJar::FabricJar { contained_jars, .. } = effective_mod.selected.jar {
+				for (name, contained_jar) in contained_jars {
+					if let TraversedJar::FabricJar { mod_id, .. } = contained_jar {
+						let mut path = effective_mod.selected.path.clone();
+						path.push(name);
+						candidates.entry(mod_id).or_default().push(ModCopy {
+							jar: contained_jar,
+							path,
+						});
+					}
+				}
+			}
+		}
+
+		let next: BTreeMap<&str, EffectiveMod> = candidates
+			.into_iter()
+			.map(|(mod_id, copies)| {
+				let selected = copies
+					.iter()
+					.max_by(|a, b| {
+						// Top-level copies always win, then the highest version, then the first copy found
+						(!a.is_nested())
+							.cmp(&!b.is_nested())
+							.then_with(|| version::compare(a.version(), b.version()))
+							.then_with(|| b.path.cmp(&a.path))
+					})
+					.cloned()
+					.unwrap();
+				(mod_id, EffectiveMod { selected, copies })
+			})
+			.collect();
+
+		let stable = next.len() == effective.len()
+			&& next
+				.iter()
+				.zip(&effective)
+				.all(|((a_id, a), (b_id, b))| a_id == b_id && a.selected.path == b.selected.path);
+		effective = next;
+		if stable {
+			break;
+		}
+	}
+	effective
+}
+
+/// Lists the mods Fabric Loader would load from the current folder, and the jar each one is loaded from
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct FlattenCommand {}
+
+impl JarCommand for FlattenCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let effective = effective_mods(&processed_jars);
+		for (mod_id, effective_mod) in &effective {
+			print!(
+				"{} {} from {}",
+				mod_id,
+				effective_mod.selected.version(),
+				effective_mod.selected.path.join(" > ")
+			);
+			let top_level_copies = effective_mod.copies.iter().filter(|copy| !copy.is_nested()).count();
+			if top_level_copies > 1 {
+				print!(
+					"{}",
+					color::red(&format!(" (duplicated in {} top-level jars!)", top_level_copies))
+				);
+			} else if effective_mod.copies.len() > 1 {
+				print!(" ({} other copies ignored)", effective_mod.copies.len() - 1);
+			}
+			println!();
+		}
+		if effective.is_empty() {
+			println!("No valid jars found!");
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,145 @@
+use crate::{file_name, DetailLevel, JarCommand, RunContext, TraversedJar};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+use std::path::PathBuf;
+
+pub const ALGORITHMS: &[&str] = &["sha1", "sha256", "sha512", "murmur2"];
+
+pub fn to_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Computes CurseForge's file fingerprint: a 32-bit MurmurHash2 (seed 1) of the file with all whitespace bytes removed
+pub fn curseforge_fingerprint(data: &[u8]) -> u32 {
+	const M: u32 = 0x5bd1_e995;
+	const R: u32 = 24;
+
+	let data: Vec<u8> = data.iter().cloned().filter(|b| !matches!(b, 9 | 10 | 13 | 32)).collect();
+	let mut h = 1 ^ data.len() as u32;
+	let mut chunks = data.chunks_exact(4);
+	for chunk in &mut chunks {
+		let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+		k = k.wrapping_mul(M);
+		k ^= k >> R;
+		k = k.wrapping_mul(M);
+		h = h.wrapping_mul(M);
+		h ^= k;
+	}
+
+	let tail = chunks.remainder();
+	if tail.len() >= 3 {
+		h ^= (tail[2] as u32) << 16;
+	}
+	if tail.len() >= 2 {
+		h ^= (tail[1] as u32) << 8;
+	}
+	if !tail.is_empty() {
+		h ^= tail[0] as u32;
+		h = h.wrapping_mul(M);
+	}
+
+	h ^= h >> 13;
+	h = h.wrapping_mul(M);
+	h ^= h >> 15;
+	h
+}
+
+/// Computes the hash of `data` with the given algorithm (one of [ALGORITHMS]), formatted the way each platform expects
+pub fn hash(algorithm: &str, data: &[u8]) -> String {
+	match algorithm {
+		"sha1" => to_hex(&Sha1::digest(data)),
+		"sha256" => to_hex(&Sha256::digest(data)),
+		"sha512" => to_hex(&Sha512::digest(data)),
+		"murmur2" => curseforge_fingerprint(data).to_string(),
+		_ => unreachable!("unknown hash algorithm {}", algorithm),
+	}
+}
+
+/// Prints hashes and CurseForge fingerprints of jars in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct HashCommand {
+	/// The algorithms to use, separated by commas (defaults to all of them)
+	#[clap(short, long, possible_values = ALGORITHMS, use_delimiter = true)]
+	algorithm: Vec<String>,
+	/// Print the hashes as JSON
+	#[clap(long)]
+	json: bool,
+}
+
+impl JarCommand for HashCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("hash")
+	}
+
+	fn is_scripted(&self) -> bool {
+		self.json
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let algorithms: Vec<&str> = if self.algorithm.is_empty() {
+			ALGORITHMS.to_vec()
+		} else {
+			ALGORITHMS
+				.iter()
+				.cloned()
+				.filter(|a| self.algorithm.iter().any(|b| a == b))
+				.collect()
+		};
+
+		let mut rows = vec![];
+		for jar in &processed_jars {
+			let contents = std::fs::read(&jar.0)?;
+			let hashes: Vec<String> = algorithms.iter().map(|algorithm| hash(algorithm, &contents)).collect();
+			rows.push((file_name(&jar.0), hashes));
+		}
+		rows.sort();
+
+		if self.json {
+			let output: Vec<_> = rows
+				.iter()
+				.map(|(file_name, hashes)| {
+					let mut entry = serde_json::Map::new();
+					entry.insert("file".to_owned(), (*file_name).into());
+					for (algorithm, hash) in algorithms.iter().zip(hashes) {
+						// CurseForge fingerprints are numbers in its API, so keep them as numbers here
+						let value = match algorithm {
+							&"murmur2" => hash.parse::<u32>().unwrap().into(),
+							_ => hash.as_str().into(),
+						};
+						entry.insert((*algorithm).to_owned(), value);
+					}
+					entry
+				})
+				.collect();
+			println!("{}", serde_json::to_string_pretty(&output)?);
+		} else {
+			let file_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0).max("File".len());
+			let widths: Vec<usize> = algorithms
+				.iter()
+				.enumerate()
+				.map(|(i, algorithm)| rows.iter().map(|row| row.1[i].len()).max().unwrap_or(0).max(algorithm.len()))
+				.collect();
+			let mut header = format!("{:width$}", "File", width = file_width);
+			for (algorithm, width) in algorithms.iter().zip(&widths) {
+				header.push_str(&format!("  {:width$}", algorithm, width = width));
+			}
+			println!("{}", header.trim_end());
+			for (file_name, hashes) in &rows {
+				let mut line = format!("{:width$}", file_name, width = file_width);
+				for (hash, width) in hashes.iter().zip(&widths) {
+					line.push_str(&format!("  {:width$}", hash, width = width));
+				}
+				println!("{}", line.trim_end());
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,90 @@
+//! Extracting the icons of mods
+
+use crate::{file_name, read_mod_json, walk_archives, DetailLevel, Icon, JarCommand, RunContext, TraversedJar};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{collections::BTreeSet, io::Read, path::PathBuf};
+
+/// Extracts the icons of mods in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct IconsCommand {
+	/// The folder to write icons to, as <mod id>.png
+	#[clap(long, default_value = "icons")]
+	out_dir: PathBuf,
+}
+
+impl JarCommand for IconsCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("icons")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		std::fs::create_dir_all(&self.out_dir)?;
+
+		let mut extracted = BTreeSet::new();
+		let mut missing = BTreeSet::new();
+		let mut broken = BTreeSet::new();
+		for jar in &processed_jars {
+			walk_archives(&jar.0, &mut vec![file_name(&jar.0).to_owned()], scan, &mut |path, zip| {
+				let fabric_mod_json = match read_mod_json(zip) {
+					Ok(fabric_mod_json) => fabric_mod_json,
+					Err(_) => return Ok(()),
+				};
+				let location = (fabric_mod_json.id.clone(), path.last().unwrap().clone());
+				if extracted.contains(&fabric_mod_json.id) {
+					return Ok(());
+				}
+
+				let icon_path = match fabric_mod_json.icon.as_ref().and_then(Icon::largest) {
+					Some(icon_path) => icon_path.trim_start_matches('/'),
+					None => {
+						missing.insert(location);
+						return Ok(());
+					}
+				};
+				let mut contents = vec![];
+				match zip.by_name(icon_path) {
+					Ok(mut file) => file.read_to_end(&mut contents)?,
+					Err(_) => {
+						broken.insert((location, format!("{} not found", icon_path)));
+						return Ok(());
+					}
+				};
+				if !contents.starts_with(b"\x89PNG\r\n\x1a\n") {
+					broken.insert((location, format!("{} is not a PNG image", icon_path)));
+					return Ok(());
+				}
+
+				std::fs::write(self.out_dir.join(format!("{}.png", fabric_mod_json.id)), contents)?;
+				extracted.insert(fabric_mod_json.id);
+				Ok(())
+			})?;
+		}
+
+		// Other copies of a mod may have had a working icon
+		missing.retain(|(mod_id, _)| !extracted.contains(mod_id));
+		broken.retain(|((mod_id, _), _)| !extracted.contains(mod_id));
+
+		println!("Extracted {} icons to {}", extracted.len(), self.out_dir.display());
+		if !missing.is_empty() {
+			println!("Mods without an icon:");
+			for (mod_id, file_name) in &missing {
+				println!("    {} ({})", mod_id, file_name);
+			}
+		}
+		if !broken.is_empty() {
+			println!("Mods with broken icons:");
+			for ((mod_id, file_name), reason) in &broken {
+				println!("    {} ({}): {}", mod_id, file_name, reason);
+			}
+		}
+
+		Ok(())
+	}
+}
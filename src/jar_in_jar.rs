@@ -0,0 +1,388 @@
+//! Listing the jars that mods bundle inside themselves
+
+use crate::{
+	color, display_name, file_name, flatten, format_size, loader_mod_ids, loader_names, version, walk_jars, DetailLevel,
+	JarCommand, RunContext, TraversedJar, EXIT_NO_MATCHES,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	cmp::Ordering,
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	process,
+};
+
+/// Displays the Jar in Jar tree for the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct JarInJarCommand {
+	/// Display the reverse tree, only showing jars which are contained by other jars
+	#[clap(short, long)]
+	reverse: bool,
+	/// With --reverse, only show mods bundled by multiple jars, with the versions each jar bundles and the copy that
+	/// Fabric Loader selects
+	#[clap(long, requires = "reverse")]
+	conflicts: bool,
+	/// List the nested jars that are redundant because Fabric Loader loads another copy of the same or a newer version,
+	/// with how much space removing them would save
+	#[clap(long, conflicts_with_all = &["reverse", "contains"])]
+	suggest_dedupe: bool,
+	/// Filter the list of top-level mods (by mod id) using this search string
+	#[clap(long)]
+	filter: Option<String>,
+	/// Only list the top-level jars that contain the mod with this id
+	#[clap(long)]
+	contains: Option<String>,
+	/// Don't print anything, exit with status 1 if no jar contains the mod given to --contains
+	#[clap(long, requires = "contains")]
+	exit_code: bool,
+}
+
+impl JarCommand for JarInJarCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn is_scripted(&self) -> bool {
+		self.exit_code
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		if let Some(ref contains) = self.contains {
+			let mut found = false;
+			for jar in &processed_jars {
+				let mut contained = false;
+				walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, _, parent| {
+					if let TraversedJar::FabricJar { mod_id, .. } = nested {
+						contained |= parent.is_some() && mod_id == contains;
+					}
+				});
+				if !contained {
+					continue;
+				}
+
+				found = true;
+				if !self.exit_code {
+					match &jar.1 {
+						TraversedJar::FabricJar { mod_id, .. } => {
+							println!("{} ({})", color::bold(mod_id), file_name(&jar.0))
+						}
+						TraversedJar::OtherLoaderJar { mods, .. } => {
+							println!(
+								"{} ({}) [{}]",
+								color::bold(&loader_mod_ids(mods)),
+								file_name(&jar.0),
+								loader_names(mods)
+							)
+						}
+						TraversedJar::NonMod => println!("{} (Not a mod)", file_name(&jar.0)),
+					}
+				}
+			}
+			if !found {
+				if self.exit_code {
+					process::exit(1);
+				}
+				println!("No jars containing {} found!", contains);
+			}
+		} else if self.suggest_dedupe {
+			let effective = flatten::effective_mods(&processed_jars);
+			let mut matched = false;
+			let mut redundant = 0;
+			let mut saved = 0;
+			for (mod_id, effective_mod) in &effective {
+				if let Some(ref filter) = self.filter {
+					if !mod_id.to_lowercase().contains(filter.to_lowercase().as_str()) {
+						continue;
+					}
+				}
+				matched = true;
+				let selected = &effective_mod.selected;
+				// Nested copies of a loaded copy are left out of the effective mods, so nothing is counted twice
+				for copy in &effective_mod.copies {
+					if !copy.is_nested()
+						|| copy.path == selected.path
+						|| version::compare(copy.version(), selected.version()) == Ordering::Greater
+					{
+						continue;
+					}
+					redundant += 1;
+					saved += copy.size();
+					println!(
+						"{} {} in {} ({}): {} {} is loaded from {}",
+						color::bold(mod_id),
+						copy.version(),
+						copy.path.join(" > "),
+						format_size(copy.size()),
+						mod_id,
+						selected.version(),
+						selected.path.join(" > ")
+					);
+				}
+			}
+			if redundant == 0 {
+				if self.filter.is_some() && !matched {
+					println!("No jars that match the given filter found!");
+					process::exit(EXIT_NO_MATCHES);
+				}
+				println!("No redundant nested jars found!");
+			} else {
+				println!(
+					"{} redundant nested jars, removing them would save {}",
+					redundant,
+					format_size(saved)
+				);
+			}
+		} else if self.conflicts {
+			struct BundledCopy<'a> {
+				jar: &'a TraversedJar,
+				version: &'a str,
+				/// The bundling mod and the top-level jar it is in, or the file name of a top-level copy
+				source: String,
+			}
+
+			let mut copies: BTreeMap<&str, Vec<BundledCopy>> = BTreeMap::new();
+			for jar in &processed_jars {
+				walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, nested_file_name, parent| {
+					if let TraversedJar::FabricJar { mod_id, mod_version, .. } = nested {
+						let source = match parent {
+							Some(parent) => format!("{} ({})", parent, file_name(&jar.0)),
+							None => format!("{} (top-level)", nested_file_name),
+						};
+						copies.entry(mod_id).or_default().push(BundledCopy {
+							jar: nested,
+							version: mod_version,
+							source,
+						});
+					}
+				});
+			}
+
+			let effective = flatten::effective_mods(&processed_jars);
+			let mut matched = false;
+			for (mod_id, copies) in &mut copies {
+				if let Some(ref filter) = self.filter {
+					if !mod_id.to_lowercase().contains(filter.to_lowercase().as_str()) {
+						continue;
+					}
+				}
+				let bundling_jars: BTreeSet<&str> = copies.iter().map(|copy| copy.source.as_str()).collect();
+				if copies.iter().all(|copy| copy.source.ends_with("(top-level)")) || bundling_jars.len() < 2 {
+					continue;
+				}
+				matched = true;
+
+				copies.sort_by(|a, b| version::compare(b.version, a.version).then_with(|| a.source.cmp(&b.source)));
+				let selected = effective.get(mod_id).map(|effective_mod| effective_mod.selected.jar);
+				match effective.get(mod_id) {
+					Some(effective_mod) => println!(
+						"{}: Fabric Loader selects {} from {}",
+						color::bold(mod_id),
+						effective_mod.selected.version(),
+						effective_mod.selected.path.join(" > ")
+					),
+					None => println!("{}: Not loaded, as no jar bundling it is loaded", color::bold(mod_id)),
+				}
+				for copy in copies.iter() {
+					let marker = if selected.is_some_and(|selected| std::ptr::eq(selected, copy.jar)) {
+						" (selected)"
+					} else {
+						""
+					};
+					println!("    {}: {}{}", copy.version, copy.source, marker);
+				}
+				// A top-level copy always wins, even when a nested copy is newer
+				if let Some(selected) = effective.get(mod_id) {
+					let newest = copies[0].version;
+					if version::compare(selected.selected.version(), newest) == Ordering::Less {
+						println!(
+							"    {}",
+							color::red(&format!(
+								"{} {} shadows the newer bundled {}",
+								mod_id,
+								selected.selected.version(),
+								newest
+							))
+						);
+					}
+				}
+			}
+			if !matched {
+				if self.filter.is_some() {
+					println!("No jars that match the given filter found!");
+					process::exit(EXIT_NO_MATCHES);
+				}
+				println!("No mods bundled by multiple jars found!");
+			}
+		} else if self.reverse {
+			struct FabricMod {
+				file_names: BTreeSet<String>,
+				parent_ids: BTreeSet<String>,
+			}
+
+			let mut reverse_tree: BTreeMap<String, FabricMod> = BTreeMap::new();
+
+			fn build_recurse(jar: TraversedJar, file_name: &str, parent: Option<&str>, tree: &mut BTreeMap<String, FabricMod>) {
+				match jar {
+					TraversedJar::NonMod => {}
+					TraversedJar::OtherLoaderJar { mods, .. } => {
+						for forge_mod in mods {
+							let entry = tree.entry(forge_mod.mod_id).or_insert(FabricMod {
+								file_names: BTreeSet::new(),
+								parent_ids: BTreeSet::new(),
+							});
+							entry.file_names.insert(file_name.to_owned());
+						}
+					}
+					TraversedJar::FabricJar {
+						mod_id,
+						contained_jars,
+						provides,
+						..
+					} => {
+						let aliases = provides
+							.iter()
+							.map(|provided| (provided, format!("{} (provided by {})", file_name, mod_id)));
+						for (id, file_name) in std::iter::once((&mod_id, file_name.to_string())).chain(aliases) {
+							let entry = tree.entry(id.clone()).or_insert(FabricMod {
+								file_names: BTreeSet::new(),
+								parent_ids: BTreeSet::new(),
+							});
+
+							entry.file_names.insert(file_name);
+							if let Some(parent) = parent {
+								entry.parent_ids.insert(parent.to_owned());
+							}
+						}
+						for jar in contained_jars {
+							build_recurse(jar.1, jar.0.as_str(), Some(mod_id.as_str()), tree);
+						}
+					}
+				}
+			}
+
+			fn print_recurse(id: &str, tree: &BTreeMap<String, FabricMod>, padding: usize) {
+				let mod_data = &tree[id];
+
+				// Don't print on first level if it has no parents
+				if padding == 0 && mod_data.parent_ids.is_empty() {
+					return;
+				}
+
+				println!(
+					"{}{} ({})",
+					"    ".repeat(padding),
+					color::bold(id),
+					mod_data.file_names.iter().cloned().collect::<Vec<_>>().join(", ")
+				);
+				for parent_id in &mod_data.parent_ids {
+					print_recurse(parent_id, tree, padding + 1);
+				}
+			}
+
+			for jar in processed_jars {
+				build_recurse(jar.1, &display_name(&jar.0), None, &mut reverse_tree);
+			}
+
+			let mut matched = false;
+			for jar in &reverse_tree {
+				if let Some(ref filter) = self.filter {
+					if !jar.0.to_lowercase().contains(filter.to_lowercase().as_str()) {
+						continue;
+					}
+				}
+				matched = true;
+				print_recurse(jar.0, &reverse_tree, 0);
+			}
+			if self.filter.is_some() && !matched {
+				println!("No jars that match the given filter found!");
+				process::exit(EXIT_NO_MATCHES);
+			}
+		} else {
+			/// Width of the indented id (or file name, for jars that aren't mods) column of a jar and its nested jars
+			fn id_width(jar: &TraversedJar, name: &str, padding: usize) -> usize {
+				match jar {
+					TraversedJar::NonMod => padding * 4 + name.chars().count(),
+					TraversedJar::OtherLoaderJar { mods, .. } => padding * 4 + loader_mod_ids(mods).chars().count(),
+					TraversedJar::FabricJar {
+						mod_id, contained_jars, ..
+					} => contained_jars
+						.iter()
+						.map(|(name, jar)| id_width(jar, name, padding + 1))
+						.fold(padding * 4 + mod_id.chars().count(), usize::max),
+				}
+			}
+
+			fn print_recurse(jar: TraversedJar, name: &str, padding: usize, width: usize) {
+				let indent = "    ".repeat(padding);
+				match jar {
+					TraversedJar::NonMod => {
+						println!(
+							"{}{} (Not a mod)",
+							indent,
+							color::padded(name, width - indent.len(), str::to_owned)
+						);
+					}
+					TraversedJar::OtherLoaderJar { mods, .. } => {
+						println!(
+							"{}{} ({}) [{}]",
+							indent,
+							color::padded(&loader_mod_ids(&mods), width - indent.len(), color::bold),
+							name,
+							loader_names(&mods)
+						);
+					}
+					TraversedJar::FabricJar {
+						mod_id, contained_jars, ..
+					} => {
+						println!(
+							"{}{} ({})",
+							indent,
+							color::padded(&mod_id, width - indent.len(), color::bold),
+							name
+						);
+						for jar in contained_jars {
+							print_recurse(jar.1, jar.0.as_str(), padding + 1, width);
+						}
+					}
+				}
+			}
+
+			let filtered_jars: Vec<_> = processed_jars
+				.into_iter()
+				.filter(|jar| match (&self.filter, &jar.1) {
+					(Some(filter), TraversedJar::FabricJar { mod_id, .. }) => {
+						mod_id.to_lowercase().contains(filter.to_lowercase().as_str())
+					}
+					(Some(filter), TraversedJar::OtherLoaderJar { mods, .. }) => mods
+						.iter()
+						.any(|forge_mod| forge_mod.mod_id.to_lowercase().contains(filter.to_lowercase().as_str())),
+					_ => true,
+				})
+				.map(|jar| (display_name(&jar.0), jar.1))
+				.collect();
+			let width = filtered_jars
+				.iter()
+				.map(|(name, jar)| id_width(jar, name, 0))
+				.max()
+				.unwrap_or_default();
+			let matched = filtered_jars
+				.iter()
+				.any(|(_, jar)| matches!(jar, TraversedJar::FabricJar { .. } | TraversedJar::OtherLoaderJar { .. }));
+			for (name, jar) in filtered_jars {
+				print_recurse(jar, &name, 0, width);
+			}
+			if self.filter.is_some() && !matched {
+				println!("No jars that match the given filter found!");
+				process::exit(EXIT_NO_MATCHES);
+			}
+		}
+
+		Ok(())
+	}
+}
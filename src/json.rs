@@ -0,0 +1,80 @@
+//! Parsing of the malformed JSON found in real-world mods, which can have a byte order mark, comments or trailing commas
+
+use serde::de::DeserializeOwned;
+
+/// Removes a byte order mark, `//` and `/* */` comments and trailing commas from JSON, leaving strings untouched
+pub fn strip_leniencies(contents: &str) -> String {
+	let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+
+	// Comments are removed first, so that a trailing comma followed by a comment is found
+	let mut without_comments = String::with_capacity(contents.len());
+	let mut chars = contents.chars().peekable();
+	let mut in_string = false;
+	while let Some(c) = chars.next() {
+		if in_string {
+			without_comments.push(c);
+			match c {
+				'\\' => without_comments.extend(chars.next()),
+				'"' => in_string = false,
+				_ => {}
+			}
+			continue;
+		}
+		match (c, chars.peek()) {
+			('"', _) => {
+				in_string = true;
+				without_comments.push(c);
+			}
+			('/', Some('/')) => while chars.next_if(|&next| next != '\n').is_some() {},
+			('/', Some('*')) => {
+				chars.next();
+				let mut previous = ' ';
+				for next in chars.by_ref() {
+					if previous == '*' && next == '/' {
+						break;
+					}
+					previous = next;
+				}
+				without_comments.push(' ');
+			}
+			_ => without_comments.push(c),
+		}
+	}
+
+	let chars: Vec<char> = without_comments.chars().collect();
+	let mut output = String::with_capacity(without_comments.len());
+	let mut in_string = false;
+	let mut escaped = false;
+	for (i, &c) in chars.iter().enumerate() {
+		if in_string {
+			if escaped {
+				escaped = false;
+			} else if c == '\\' {
+				escaped = true;
+			} else if c == '"' {
+				in_string = false;
+			}
+		} else if c == '"' {
+			in_string = true;
+		} else if c == ',' && matches!(chars[i + 1..].iter().find(|c| !c.is_whitespace()), Some(']' | '}')) {
+			continue;
+		}
+		output.push(c);
+	}
+	output
+}
+
+/// Parses JSON, retrying with [`strip_leniencies`] if it isn't valid. Returns whether the retry was needed along with
+/// the value, or the error from the first attempt if both fail.
+pub fn from_str_lenient<T: DeserializeOwned>(contents: &str) -> serde_json::Result<(T, bool)> {
+	match serde_json::from_str(contents) {
+		Ok(value) => Ok((value, false)),
+		Err(err) => {
+			let stripped = strip_leniencies(contents);
+			if stripped == contents {
+				return Err(err);
+			}
+			serde_json::from_str(&stripped).map(|value| (value, true)).map_err(|_| err)
+		}
+	}
+}
@@ -0,0 +1,139 @@
+//! Listing the translations that mods ship
+
+use crate::{color, walk_labelled_archives, DetailLevel, JarCommand, RunContext, TraversedJar, EXIT_UNREADABLE_JARS};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	process,
+};
+
+/// Lists the translations in each mod in the current folder, with how many of the `en_us` keys they translate
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct LangCommand {
+	/// Only show the coverage of this locale, such as `de_de`
+	#[clap(long)]
+	locale: Option<String>,
+}
+
+impl JarCommand for LangCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("lang")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		struct ModLang {
+			paths: Vec<String>,
+			/// Translation keys (prefixed with the namespace) of each locale
+			locales: BTreeMap<String, BTreeSet<String>>,
+			/// Lang files that aren't valid JSON
+			invalid: Vec<String>,
+		}
+
+		let target = self.locale.as_ref().map(|locale| locale.to_lowercase());
+		let (walked, unreadable) = walk_labelled_archives(&processed_jars, scan, "lang files", |zip| {
+			let mut lang = ModLang {
+				paths: vec![],
+				locales: BTreeMap::new(),
+				invalid: vec![],
+			};
+			let lang_files: Vec<String> = zip
+				.file_names()
+				.filter(|name| {
+					let segments: Vec<&str> = name.split('/').collect();
+					segments.len() == 4 && segments[0] == "assets" && segments[2] == "lang" && name.ends_with(".json")
+				})
+				.map(str::to_owned)
+				.collect();
+			for name in lang_files {
+				let segments: Vec<&str> = name.split('/').collect();
+				let namespace = segments[1];
+				// Locale codes were upper case (e.g. en_US) before 1.11
+				let locale = segments[3].trim_end_matches(".json").to_lowercase();
+				let keys: BTreeMap<String, serde_json::Value> = match serde_json::from_reader(zip.by_name(&name)?) {
+					Ok(keys) => keys,
+					Err(_) => {
+						lang.invalid.push(name.clone());
+						continue;
+					}
+				};
+				lang.locales
+					.entry(locale)
+					.or_default()
+					.extend(keys.into_keys().map(|key| format!("{}:{}", namespace, key)));
+			}
+			Ok(lang)
+		});
+
+		let mut mods: BTreeMap<String, ModLang> = BTreeMap::new();
+		for (label, path, lang) in walked {
+			// Only compare the lang files of the first copy of each mod
+			mods.entry(label).or_insert(lang).paths.push(path);
+		}
+
+		let mut found = false;
+		for (label, lang) in &mods {
+			if lang.locales.is_empty() && lang.invalid.is_empty() {
+				continue;
+			}
+			found = true;
+			let reference = lang.locales.get("en_us");
+			match reference {
+				Some(reference) => println!(
+					"{} ({}): {} keys, {} locales",
+					color::bold(label),
+					lang.paths.join(", "),
+					reference.len(),
+					lang.locales.len()
+				),
+				None => println!(
+					"{} ({}): {} locales, no en_us to compare to",
+					color::bold(label),
+					lang.paths.join(", "),
+					lang.locales.len()
+				),
+			}
+			for (locale, keys) in &lang.locales {
+				if target.as_ref().is_some_and(|target| target != locale) || (target.is_none() && locale == "en_us") {
+					continue;
+				}
+				match reference {
+					Some(reference) if !reference.is_empty() => {
+						let translated = reference.intersection(keys).count();
+						let coverage = format!("{}/{} ({}%)", translated, reference.len(), translated * 100 / reference.len());
+						let coverage = if translated == reference.len() {
+							coverage
+						} else {
+							color::yellow(&coverage)
+						};
+						println!("    {}: {}", locale, coverage);
+					}
+					_ => println!("    {}: {} keys", locale, keys.len()),
+				}
+			}
+			if let Some(target) = &target {
+				if !lang.locales.contains_key(target) {
+					println!("    {}", color::red(&format!("{}: not translated", target)));
+				}
+			}
+			for name in &lang.invalid {
+				println!("    {}", color::red(&format!("{} isn't valid JSON", name)));
+			}
+		}
+		if !found {
+			println!("No mods with lang files found!");
+		}
+		if unreadable {
+			process::exit(EXIT_UNREADABLE_JARS);
+		}
+
+		Ok(())
+	}
+}
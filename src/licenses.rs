@@ -0,0 +1,144 @@
+//! Listing who made the mods in a folder and under which licenses
+
+use crate::{file_name, print_contacts, walk_jars, DetailLevel, JarCommand, RunContext, TraversedJar, EXIT_NO_MATCHES};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	process,
+};
+
+/// Groups mods in the current folder by their declared license
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct LicensesCommand {
+	/// Warn about mods using any of these licenses, separated by commas
+	#[clap(long, use_delimiter = true)]
+	deny: Vec<String>,
+}
+
+impl JarCommand for LicensesCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let mut by_license: BTreeMap<&str, BTreeSet<(&str, &str)>> = BTreeMap::new();
+		let mut unlicensed: BTreeMap<(&str, &str), &[String]> = BTreeMap::new();
+		let mut denied: BTreeSet<(&str, &str, &str)> = BTreeSet::new();
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, _| {
+				if let TraversedJar::FabricJar {
+					mod_id,
+					licenses,
+					license_files,
+					..
+				} = nested
+				{
+					if licenses.is_empty() {
+						unlicensed.insert((mod_id, file_name), license_files);
+					}
+					for license in licenses {
+						by_license.entry(license).or_default().insert((mod_id, file_name));
+						if self.deny.iter().any(|denied| denied.eq_ignore_ascii_case(license)) {
+							denied.insert((mod_id, file_name, license));
+						}
+					}
+				}
+			});
+		}
+
+		for (license, mods) in &by_license {
+			println!("{}", license);
+			for (mod_id, file_name) in mods {
+				println!("    {} ({})", mod_id, file_name);
+			}
+		}
+		if !unlicensed.is_empty() {
+			println!("No license declared:");
+			for ((mod_id, file_name), license_files) in &unlicensed {
+				if license_files.is_empty() {
+					println!("    {} ({})", mod_id, file_name);
+				} else {
+					println!("    {} ({}, bundles {})", mod_id, file_name, license_files.join(", "));
+				}
+			}
+		}
+		if !denied.is_empty() {
+			println!("Warning: mods using denied licenses found!");
+			for (mod_id, file_name, license) in &denied {
+				println!("    {} ({}): {}", mod_id, file_name, license);
+			}
+		}
+		if by_license.is_empty() && unlicensed.is_empty() {
+			println!("No valid jars found!");
+		}
+
+		Ok(())
+	}
+}
+
+/// Prints the authors and contact information of mods in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct ContactsCommand {
+	/// Filter the list of mods (by mod id) using this search string
+	#[clap(long)]
+	filter: Option<String>,
+}
+
+impl JarCommand for ContactsCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let mut mods = BTreeMap::new();
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, _| {
+				if let TraversedJar::FabricJar { mod_id, .. } = nested {
+					if let Some(ref filter) = self.filter {
+						if !mod_id.to_lowercase().contains(filter.to_lowercase().as_str()) {
+							return;
+						}
+					}
+					mods.entry(mod_id.as_str())
+						.or_insert((nested, BTreeSet::new()))
+						.1
+						.insert(file_name);
+				}
+			});
+		}
+
+		for (mod_id, (jar, file_names)) in &mods {
+			if let TraversedJar::FabricJar {
+				authors,
+				contributors,
+				contact,
+				..
+			} = jar
+			{
+				println!("{} ({})", mod_id, file_names.iter().cloned().collect::<Vec<_>>().join(", "));
+				if authors.is_empty() && contributors.is_empty() && contact.is_empty() {
+					println!("    No authors or contact information declared");
+				}
+				print_contacts(authors, contributors, contact);
+			}
+		}
+		if mods.is_empty() {
+			if self.filter.is_some() {
+				println!("No jars that match the given filter found!");
+				process::exit(EXIT_NO_MATCHES);
+			} else {
+				println!("No valid jars found!");
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,486 @@
+use crate::{
+	color, display_name, json,
+	version::{Version, VersionRange},
+	DetailLevel, JarCommand, JarContents, RunContext, TraversedJar, EXIT_LINT_ERRORS,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use serde_json::{Map, Value};
+use std::{
+	fmt,
+	io::{Read, Seek},
+	path::PathBuf,
+	process,
+};
+use zip::ZipArchive;
+
+/// Fields that Fabric Loader reads from a schema version 1 fabric.mod.json
+const KNOWN_FIELDS: &[&str] = &[
+	"schemaVersion",
+	"id",
+	"version",
+	"name",
+	"description",
+	"authors",
+	"contributors",
+	"contact",
+	"license",
+	"icon",
+	"environment",
+	"entrypoints",
+	"jars",
+	"languageAdapters",
+	"mixins",
+	"accessWidener",
+	"depends",
+	"recommends",
+	"suggests",
+	"breaks",
+	"conflicts",
+	"provides",
+	"custom",
+];
+
+const DEPENDENCY_FIELDS: &[&str] = &["depends", "recommends", "suggests", "breaks", "conflicts"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	Error,
+	Warning,
+	/// Something that isn't a mistake, but is worth knowing about
+	Note,
+}
+
+impl fmt::Display for Severity {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Severity::Error => write!(f, "error"),
+			Severity::Warning => write!(f, "warning"),
+			Severity::Note => write!(f, "note"),
+		}
+	}
+}
+
+pub struct Problem {
+	pub severity: Severity,
+	pub message: String,
+}
+
+/// Prints each problem on an indented line, returning the number of errors and warnings
+pub fn print_problems(problems: &[Problem]) -> (usize, usize) {
+	let mut errors = 0;
+	let mut warnings = 0;
+	for problem in problems {
+		let line = format!("{}: {}", problem.severity, problem.message);
+		match problem.severity {
+			Severity::Error => {
+				errors += 1;
+				println!("    {}", color::red(&line));
+			}
+			Severity::Warning => {
+				warnings += 1;
+				println!("    {}", color::yellow(&line));
+			}
+			Severity::Note => println!("    {}", line),
+		}
+	}
+	(errors, warnings)
+}
+
+/// Whether an id matches the pattern Fabric Loader requires: a lowercase letter followed by 1 to 63 lowercase letters,
+/// digits, `-` or `_`
+fn is_valid_mod_id(id: &str) -> bool {
+	let mut chars = id.chars();
+	chars.next().is_some_and(|c| c.is_ascii_lowercase())
+		&& (2..=64).contains(&id.len())
+		&& chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+struct Linter<'a, R: Read + Seek> {
+	zip: &'a mut ZipArchive<R>,
+	problems: Vec<Problem>,
+}
+
+impl<R: Read + Seek> Linter<'_, R> {
+	fn error(&mut self, message: String) {
+		self.problems.push(Problem {
+			severity: Severity::Error,
+			message,
+		});
+	}
+
+	fn warning(&mut self, message: String) {
+		self.problems.push(Problem {
+			severity: Severity::Warning,
+			message,
+		});
+	}
+
+	/// Flags a path in fabric.mod.json that doesn't exist in the jar
+	fn check_path(&mut self, field: &str, path: &str) {
+		if self.zip.by_name(path.trim_start_matches('/')).is_err() {
+			self.error(format!("{} refers to {}, which isn't in the jar", field, path));
+		}
+	}
+
+	fn check_string_field(&mut self, fields: &Map<String, Value>, field: &str) -> Option<String> {
+		match fields.get(field) {
+			Some(Value::String(value)) => Some(value.clone()),
+			Some(_) => {
+				self.error(format!("{} must be a string", field));
+				None
+			}
+			None => {
+				self.error(format!("{} is missing", field));
+				None
+			}
+		}
+	}
+
+	fn lint(&mut self, fields: &Map<String, Value>) {
+		match fields.get("schemaVersion") {
+			Some(Value::Number(number)) if number.as_u64() == Some(1) => {}
+			Some(Value::Number(number)) if number.as_u64() == Some(0) => {
+				// The other fields have different names and shapes in version 0, so checking them would be misleading
+				self.warning("schemaVersion 0 is outdated, the current version is 1".to_owned());
+				return;
+			}
+			Some(value) => self.error(format!("schemaVersion must be 1, not {}", value)),
+			None => self.warning("schemaVersion is missing, so Fabric Loader treats the file as schema version 0".to_owned()),
+		}
+
+		if let Some(id) = self.check_string_field(fields, "id") {
+			if !is_valid_mod_id(&id) {
+				self.error(format!(
+					"id {} must start with a lowercase letter and contain 2 to 64 lowercase letters, digits, - or _",
+					id
+				));
+			}
+		}
+
+		if let Some(version) = self.check_string_field(fields, "version") {
+			if version.contains("${") {
+				self.error(format!("version {} contains a placeholder that wasn't expanded", version));
+			} else if Version::parse(&version).is_none() {
+				self.warning(format!(
+					"version {} isn't a semantic version, so version ranges can't match it",
+					version
+				));
+			}
+		}
+
+		match fields.get("icon") {
+			Some(Value::String(path)) => self.check_path("icon", path),
+			Some(Value::Object(sizes)) => {
+				for (size, path) in sizes {
+					match path.as_str() {
+						Some(path) => self.check_path(&format!("icon {}", size), path),
+						None => self.error(format!("icon {} must be a string", size)),
+					}
+				}
+			}
+			Some(_) => self.error("icon must be a string or an object of sizes to paths".to_owned()),
+			None => {}
+		}
+
+		match fields.get("accessWidener") {
+			Some(Value::String(path)) => self.check_path("accessWidener", path),
+			Some(_) => self.error("accessWidener must be a string".to_owned()),
+			None => {}
+		}
+
+		if let Some(mixins) = fields.get("mixins") {
+			for mixin in mixins.as_array().into_iter().flatten() {
+				match mixin.as_str().or_else(|| mixin.get("config").and_then(Value::as_str)) {
+					Some(config) => self.check_path("mixins", config),
+					None => self.error(format!("mixins entry {} must be a string or an object with a config", mixin)),
+				}
+			}
+			if !mixins.is_array() {
+				self.error("mixins must be an array".to_owned());
+			}
+		}
+
+		if let Some(jars) = fields.get("jars") {
+			for jar in jars.as_array().into_iter().flatten() {
+				match jar.get("file").and_then(Value::as_str) {
+					Some(file) => self.check_path("jars", file),
+					None => self.error(format!("jars entry {} must be an object with a file", jar)),
+				}
+			}
+			if !jars.is_array() {
+				self.error("jars must be an array".to_owned());
+			}
+		}
+
+		for field in DEPENDENCY_FIELDS {
+			let dependencies = match fields.get(*field) {
+				Some(Value::Object(dependencies)) => dependencies,
+				Some(_) => {
+					self.error(format!("{} must be an object of mod ids to version ranges", field));
+					continue;
+				}
+				None => continue,
+			};
+			for (id, ranges) in dependencies {
+				let ranges: Vec<&str> = match ranges {
+					Value::String(range) => vec![range],
+					Value::Array(ranges) => ranges.iter().filter_map(Value::as_str).collect(),
+					_ => vec![],
+				};
+				if ranges.is_empty() || VersionRange::parse_any(&ranges).is_none() {
+					self.error(format!(
+						"{} entry {} has an invalid version range: {}",
+						field, id, dependencies[id]
+					));
+				}
+			}
+		}
+
+		for field in fields.keys() {
+			if !KNOWN_FIELDS.contains(&field.as_str()) {
+				self.warning(format!(
+					"unknown field {}, which Fabric Loader ignores (put custom data in custom instead)",
+					field
+				));
+			}
+		}
+	}
+}
+
+/// Checks the fabric.mod.json of a jar for problems that Fabric Loader would reject or silently ignore. Returns `None`
+/// if the jar doesn't have a fabric.mod.json.
+pub fn lint_jar<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<Option<Vec<Problem>>> {
+	let mut contents = String::new();
+	match zip.by_name("fabric.mod.json") {
+		Ok(mut file) => file.read_to_string(&mut contents)?,
+		Err(_) => return Ok(None),
+	};
+
+	let mut linter = Linter { zip, problems: vec![] };
+	match json::from_str_lenient::<Value>(&contents) {
+		Ok((value, lenient)) => {
+			if lenient {
+				linter.warning(
+					"fabric.mod.json isn't strictly valid JSON, as it has a byte order mark, comments or trailing commas"
+						.to_owned(),
+				);
+			}
+			match value {
+				Value::Object(fields) => linter.lint(&fields),
+				_ => linter.error("fabric.mod.json must contain an object".to_owned()),
+			}
+		}
+		Err(e) => linter.error(format!("fabric.mod.json isn't valid JSON: {}", e)),
+	}
+	Ok(Some(linter.problems))
+}
+
+/// Parses a single field type from the start of a descriptor, returning the rest of the descriptor
+fn parse_field_type(descriptor: &str) -> Option<&str> {
+	let mut chars = descriptor.chars();
+	match chars.next()? {
+		'B' | 'C' | 'D' | 'F' | 'I' | 'J' | 'S' | 'Z' => Some(chars.as_str()),
+		'L' => {
+			let (class, rest) = chars.as_str().split_once(';')?;
+			(!class.is_empty() && !class.contains('.')).then_some(rest)
+		}
+		'[' => parse_field_type(chars.as_str()),
+		_ => None,
+	}
+}
+
+fn is_field_descriptor(descriptor: &str) -> bool {
+	parse_field_type(descriptor) == Some("")
+}
+
+fn is_method_descriptor(descriptor: &str) -> bool {
+	let mut rest = match descriptor.strip_prefix('(') {
+		Some(rest) => rest,
+		None => return false,
+	};
+	while !rest.starts_with(')') {
+		rest = match parse_field_type(rest) {
+			Some(rest) => rest,
+			None => return false,
+		};
+	}
+	let return_type = &rest[1..];
+	return_type == "V" || is_field_descriptor(return_type)
+}
+
+/// Checks the header and entries of an access widener for syntax errors, and for settings that don't work in a
+/// production jar
+pub fn lint_access_widener(contents: &str) -> Vec<Problem> {
+	let mut problems = vec![];
+	let mut problem = |severity, message| problems.push(Problem { severity, message });
+
+	let mut lines = contents.lines().enumerate();
+	let header: Vec<&str> = lines
+		.next()
+		.map(|(_, line)| line.split('#').next().unwrap_or_default().split_whitespace().collect())
+		.unwrap_or_default();
+	let version = match header.as_slice() {
+		["accessWidener", version @ ("v1" | "v2"), namespace] => {
+			if *namespace != "intermediary" {
+				problem(
+					Severity::Warning,
+					format!(
+						"the namespace is {}, but access wideners in production jars must use intermediary",
+						namespace
+					),
+				);
+			}
+			*version
+		}
+		["accessWidener", version, _] => {
+			problem(Severity::Error, format!("unknown access widener version {}", version));
+			return problems;
+		}
+		_ => {
+			problem(
+				Severity::Error,
+				"the first line must be an accessWidener header, e.g. accessWidener v1 intermediary".to_owned(),
+			);
+			return problems;
+		}
+	};
+
+	for (index, line) in lines {
+		let line_number = index + 1;
+		let fields: Vec<&str> = line.split('#').next().unwrap_or_default().split_whitespace().collect();
+		let (access, kind) = match fields.as_slice() {
+			[] => continue,
+			[access, kind, ..] => (*access, *kind),
+			_ => {
+				problem(
+					Severity::Error,
+					format!("line {}: missing the type of the entry", line_number),
+				);
+				continue;
+			}
+		};
+
+		let access = match access.strip_prefix("transitive-") {
+			Some(access) => {
+				if version == "v1" {
+					problem(
+						Severity::Error,
+						format!("line {}: transitive entries need an accessWidener v2 header", line_number),
+					);
+				} else {
+					problem(
+						Severity::Note,
+						format!(
+							"line {}: {} is transitive, so it also applies to mods that depend on this one",
+							line_number,
+							fields[1..].join(" ")
+						),
+					);
+				}
+				access
+			}
+			None => access,
+		};
+		if !["accessible", "extendable", "mutable"].contains(&access) {
+			problem(Severity::Error, format!("line {}: unknown access {}", line_number, access));
+			continue;
+		}
+
+		let (expected_fields, expected) = match kind {
+			"class" => (3, "a class name"),
+			"method" | "field" => (5, "an owner class, a name and a descriptor"),
+			_ => {
+				problem(Severity::Error, format!("line {}: unknown entry type {}", line_number, kind));
+				continue;
+			}
+		};
+		if fields.len() != expected_fields {
+			problem(
+				Severity::Error,
+				format!(
+					"line {}: {} entries need exactly {} after the type",
+					line_number, kind, expected
+				),
+			);
+			continue;
+		}
+
+		if access == "mutable" && kind != "field" {
+			problem(
+				Severity::Error,
+				format!("line {}: only fields can be made mutable", line_number),
+			);
+		}
+		if access == "extendable" && kind == "field" {
+			problem(
+				Severity::Error,
+				format!("line {}: fields can't be made extendable", line_number),
+			);
+		}
+		if fields[2].contains('.') {
+			problem(
+				Severity::Error,
+				format!("line {}: class name {} must use / instead of .", line_number, fields[2]),
+			);
+		}
+		let valid_descriptor = match kind {
+			"method" => is_method_descriptor(fields[4]),
+			"field" => is_field_descriptor(fields[4]),
+			_ => true,
+		};
+		if !valid_descriptor {
+			problem(
+				Severity::Error,
+				format!("line {}: {} isn't a valid {} descriptor", line_number, fields[4], kind),
+			);
+		}
+	}
+	problems
+}
+
+/// Checks the fabric.mod.json of each jar in the current folder for mistakes, such as invalid ids or versions and
+/// paths to files that aren't in the jar
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct LintCommand {
+	/// Also exit with a non-zero status if there are only warnings
+	#[clap(long)]
+	strict: bool,
+}
+
+impl JarCommand for LintCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("lint")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		let mut errors = 0;
+		let mut warnings = 0;
+		for jar in &processed_jars {
+			let problems = match lint_jar(&mut JarContents::open(&jar.0, scan)?.archive()?)? {
+				Some(problems) if !problems.is_empty() => problems,
+				_ => continue,
+			};
+			println!("{}:", display_name(&jar.0));
+			let (jar_errors, jar_warnings) = print_problems(&problems);
+			errors += jar_errors;
+			warnings += jar_warnings;
+		}
+		if errors == 0 && warnings == 0 {
+			println!("No problems found");
+		} else {
+			println!("{} errors, {} warnings", errors, warnings);
+			if errors > 0 || self.strict {
+				process::exit(EXIT_LINT_ERRORS);
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,390 @@
+//! Listing the mods in a folder and the details of a single mod
+
+use crate::{
+	color, csv_list, csv_row, display_name, file_name, flatten, is_csv, lookup, print_contacts, sort_fields, walk_jars,
+	DetailLevel, JarCommand, RunContext, SortOptions, TraversedJar,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	process,
+};
+
+/// Lists all mods in the current folder, including nested mods
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct ListCommand {
+	/// Only print mod ids, one per line
+	#[clap(long, conflicts_with_all = &["markdown", "format"])]
+	pub ids_only: bool,
+	/// Print a Markdown table of mods, for pasting into a README or chat message
+	#[clap(long, conflicts_with = "format")]
+	pub markdown: bool,
+	/// Add a column with the Modrinth project URL of each mod to the Markdown table, looked up by hash
+	#[clap(long, requires = "markdown")]
+	modrinth: bool,
+	/// Print the output in this format instead of a human-readable listing
+	#[clap(long, possible_values = &["text", "csv"])]
+	pub format: Option<String>,
+	/// Only list the copy of each mod that Fabric Loader loads, as a table including whether it is nested
+	#[clap(long)]
+	effective: bool,
+	#[clap(flatten)]
+	sort: SortOptions,
+}
+
+impl JarCommand for ListCommand {
+	fn detail_level(&self) -> DetailLevel {
+		if self.sort.needs_mixins() || self.modrinth {
+			DetailLevel::Full
+		} else {
+			DetailLevel::Metadata
+		}
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn is_scripted(&self) -> bool {
+		self.ids_only || self.markdown || is_csv(&self.format)
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		struct FabricMod {
+			name: Option<String>,
+			versions: BTreeSet<String>,
+			environments: BTreeSet<String>,
+			file_names: BTreeSet<String>,
+			hashes: BTreeSet<String>,
+			/// Whether the (effective) copy is nested in another jar
+			nested: bool,
+		}
+
+		let effective = if self.effective {
+			flatten::effective_mods(&processed_jars)
+		} else {
+			BTreeMap::new()
+		};
+		let mut mods: BTreeMap<&str, FabricMod> = BTreeMap::new();
+		if self.effective {
+			for (mod_id, effective_mod) in &effective {
+				if let TraversedJar::FabricJar {
+					mod_name,
+					mod_version,
+					hash,
+					environment,
+					..
+				} = effective_mod.selected.jar
+				{
+					mods.insert(
+						mod_id,
+						FabricMod {
+							name: mod_name.clone(),
+							versions: BTreeSet::from([mod_version.clone()]),
+							environments: BTreeSet::from([environment.to_string()]),
+							file_names: BTreeSet::from([effective_mod.selected.path.join(" > ")]),
+							hashes: BTreeSet::from([hash.clone()]),
+							nested: effective_mod.selected.is_nested(),
+						},
+					);
+				}
+			}
+		} else {
+			for jar in &processed_jars {
+				walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, parent| {
+					if let TraversedJar::FabricJar {
+						mod_name,
+						mod_id,
+						mod_version,
+						hash,
+						environment,
+						..
+					} = nested
+					{
+						let entry = mods.entry(mod_id.as_str()).or_insert(FabricMod {
+							name: None,
+							versions: BTreeSet::new(),
+							environments: BTreeSet::new(),
+							file_names: BTreeSet::new(),
+							hashes: BTreeSet::new(),
+							nested: false,
+						});
+						if entry.name.is_none() {
+							entry.name = mod_name.clone();
+						}
+						entry.versions.insert(mod_version.clone());
+						entry.environments.insert(environment.to_string());
+						entry.file_names.insert(if parent.is_none() {
+							display_name(&jar.0)
+						} else {
+							file_name.to_owned()
+						});
+						entry.hashes.insert(hash.clone());
+					} else if let TraversedJar::OtherLoaderJar {
+						mods: forge_mods, hash, ..
+					} = nested
+					{
+						for forge_mod in forge_mods {
+							let entry = mods.entry(forge_mod.mod_id.as_str()).or_insert(FabricMod {
+								name: None,
+								versions: BTreeSet::new(),
+								environments: BTreeSet::new(),
+								file_names: BTreeSet::new(),
+								hashes: BTreeSet::new(),
+								nested: false,
+							});
+							if entry.name.is_none() {
+								entry.name = forge_mod.mod_name.clone();
+							}
+							entry
+								.versions
+								.insert(forge_mod.mod_version.clone().unwrap_or_else(|| "unknown".to_owned()));
+							entry.file_names.insert(display_name(&jar.0));
+							entry.hashes.insert(hash.clone());
+						}
+					}
+				});
+			}
+		}
+		let mods = self.sort.sorted(mods, &sort_fields(&processed_jars));
+
+		if is_csv(&self.format) {
+			let mut header = vec!["mod_id", "name", "versions", "environments", "files"];
+			if self.effective {
+				header.push("nested");
+			}
+			println!("{}", csv_row(&header));
+			for (mod_id, mod_data) in &mods {
+				let mut row = vec![
+					mod_id.to_string(),
+					csv_list(mod_data.name.as_ref()),
+					csv_list(&mod_data.versions),
+					csv_list(&mod_data.environments),
+					csv_list(&mod_data.file_names),
+				];
+				if self.effective {
+					row.push(mod_data.nested.to_string());
+				}
+				println!("{}", csv_row(&row));
+			}
+			return Ok(());
+		}
+
+		if self.markdown {
+			fn cell<'a>(values: impl IntoIterator<Item = &'a String>) -> String {
+				values.into_iter().cloned().collect::<Vec<_>>().join(", ").replace('|', "\\|")
+			}
+
+			let modrinth_matches = if self.modrinth {
+				let hashes: Vec<String> = mods
+					.iter()
+					.flat_map(|(_, mod_data)| mod_data.hashes.iter().cloned())
+					.collect();
+				lookup::modrinth_lookup(&hashes)?
+			} else {
+				BTreeMap::new()
+			};
+
+			if self.modrinth {
+				println!("| Name | Id | Version | Environment | File | Project |");
+				println!("| --- | --- | --- | --- | --- | --- |");
+			} else {
+				println!("| Name | Id | Version | Environment | File |");
+				println!("| --- | --- | --- | --- | --- |");
+			}
+			for (mod_id, mod_data) in &mods {
+				let mut row = vec![
+					cell(mod_data.name.as_ref()),
+					mod_id.replace('|', "\\|"),
+					cell(&mod_data.versions),
+					cell(&mod_data.environments),
+					cell(&mod_data.file_names),
+				];
+				if self.modrinth {
+					let url = mod_data
+						.hashes
+						.iter()
+						.find_map(|hash| modrinth_matches.get(hash))
+						.map(|found| found.url());
+					row.push(cell(url.as_ref()));
+				}
+				println!("| {} |", row.join(" | "));
+			}
+			return Ok(());
+		}
+
+		if self.effective && !self.ids_only {
+			// Effective mods have a single version, environment and file, so they can be shown as a table
+			let rows: Vec<[String; 4]> = mods
+				.iter()
+				.map(|(mod_id, mod_data)| {
+					[
+						mod_id.to_string(),
+						mod_data.name.clone().unwrap_or_default(),
+						mod_data.versions.iter().cloned().collect(),
+						mod_data.environments.iter().cloned().collect(),
+					]
+				})
+				.collect();
+			let widths: Vec<usize> = (0..4)
+				.map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap_or_default())
+				.collect();
+			for (row, (_, mod_data)) in rows.iter().zip(&mods) {
+				println!(
+					"{} {} {} {} {}{}",
+					color::padded(&row[0], widths[0], color::bold),
+					color::padded(&row[1], widths[1], str::to_owned),
+					color::padded(&row[2], widths[2], str::to_owned),
+					color::padded(&row[3], widths[3], str::to_owned),
+					mod_data.file_names.iter().cloned().collect::<String>(),
+					if mod_data.nested { " (JiJ)" } else { "" }
+				);
+			}
+			if mods.is_empty() {
+				println!("No valid jars found!");
+			}
+			return Ok(());
+		}
+
+		for (mod_id, mod_data) in &mods {
+			if self.ids_only {
+				println!("{}", mod_id);
+			} else {
+				println!(
+					"{} {} ({})",
+					mod_id,
+					mod_data.versions.iter().cloned().collect::<Vec<_>>().join(", "),
+					mod_data.file_names.iter().cloned().collect::<Vec<_>>().join(", ")
+				);
+			}
+		}
+		if mods.is_empty() && !self.ids_only {
+			println!("No valid jars found!");
+		}
+
+		Ok(())
+	}
+}
+
+/// Prints metadata for a single mod in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct InfoCommand {
+	/// The id of the mod to display
+	id: String,
+	/// Only print the value of this field, one line per distinct value
+	#[clap(
+		long,
+		possible_values = &["id", "name", "version", "environment", "file", "hash", "authors", "homepage", "sources", "issues"]
+	)]
+	field: Option<String>,
+}
+
+impl JarCommand for InfoCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn is_scripted(&self) -> bool {
+		self.field.is_some()
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let mut copies = vec![];
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, parent| {
+				if let TraversedJar::FabricJar { mod_id, .. } = nested {
+					if *mod_id == self.id {
+						copies.push((nested, file_name, parent));
+					}
+				}
+			});
+		}
+
+		if copies.is_empty() {
+			if self.field.is_none() {
+				println!("No mod with id {} found!", self.id);
+			}
+			process::exit(1);
+		}
+
+		if let Some(ref field) = self.field {
+			let mut values = BTreeSet::new();
+			for (jar, file_name, _) in &copies {
+				if let TraversedJar::FabricJar {
+					mod_name,
+					mod_id,
+					mod_version,
+					hash,
+					environment,
+					authors,
+					contact,
+					..
+				} = jar
+				{
+					match field.as_str() {
+						"id" => values.insert(mod_id.clone()),
+						"name" => values.insert(mod_name.clone().unwrap_or_else(|| mod_id.clone())),
+						"version" => values.insert(mod_version.clone()),
+						"environment" => values.insert(environment.to_string()),
+						"file" => values.insert(file_name.to_string()),
+						"hash" => values.insert(hash.clone()),
+						"authors" => values.insert(authors.join(", ")),
+						contact_field => contact.get(contact_field).is_some_and(|value| values.insert(value.clone())),
+					};
+				}
+			}
+			if values.is_empty() {
+				process::exit(1);
+			}
+			for value in values {
+				println!("{}", value);
+			}
+		} else {
+			for (jar, file_name, parent) in &copies {
+				if let TraversedJar::FabricJar {
+					mod_name,
+					mod_id,
+					mod_version,
+					hash,
+					environment,
+					mixins,
+					mixin_config_plugins,
+					contained_jars,
+					access_widener_contents,
+					authors,
+					contributors,
+					contact,
+					schema_version,
+					..
+				} = jar
+				{
+					println!("{} ({})", mod_name.as_ref().unwrap_or(mod_id), file_name);
+					println!("    Id: {}", mod_id);
+					println!("    Version: {}", mod_version);
+					println!("    Environment: {}", environment);
+					println!("    Schema version: {}", schema_version);
+					println!("    SHA-1: {}", hash);
+					if let Some(parent) = parent {
+						println!("    Bundled by: {}", parent);
+					}
+					println!("    Mixins: {}", mixins.values().map(Vec::len).sum::<usize>());
+					if !mixin_config_plugins.is_empty() {
+						println!("    Mixin plugins: {}", mixin_config_plugins.join(", "));
+					}
+					println!("    Nested jars: {}", contained_jars.len());
+					println!(
+						"    Access widener: {}",
+						if access_widener_contents.is_some() { "yes" } else { "no" }
+					);
+					print_contacts(authors, contributors, contact);
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,195 @@
+//! Listing the mod loaders that mods support and the language adapters they use
+
+use crate::{
+	color, display_name, file_name, walk_jars, DetailLevel, JarCommand, Loader, RunContext, TraversedJar, LANGUAGE_PROVIDERS,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+};
+
+/// Reports which mod loaders each jar in the current folder has metadata for
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct LoadersCommand {
+	/// Only list jars whose metadata declares different versions for different loaders
+	#[clap(long)]
+	mismatched: bool,
+}
+
+impl JarCommand for LoadersCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let mut found = false;
+		for jar in &processed_jars {
+			let mut declared: Vec<(Loader, &str, Option<&str>)> = vec![];
+			match &jar.1 {
+				TraversedJar::FabricJar {
+					mod_id,
+					mod_version,
+					other_loaders,
+					..
+				} => {
+					declared.push((Loader::Fabric, mod_id, Some(mod_version)));
+					declared.extend(other_loaders.iter().map(|loader_mod| {
+						(
+							loader_mod.loader,
+							loader_mod.mod_id.as_str(),
+							loader_mod.mod_version.as_deref(),
+						)
+					}));
+				}
+				TraversedJar::OtherLoaderJar { mods, .. } => {
+					declared.extend(mods.iter().map(|loader_mod| {
+						(
+							loader_mod.loader,
+							loader_mod.mod_id.as_str(),
+							loader_mod.mod_version.as_deref(),
+						)
+					}));
+				}
+				TraversedJar::NonMod => {}
+			}
+
+			// Compare the first mod declared for each loader, as the other entries are usually separate mods
+			let mut versions: BTreeMap<Loader, &str> = BTreeMap::new();
+			for (loader, _, version) in &declared {
+				if let Some(version) = version {
+					versions.entry(*loader).or_insert(version);
+				}
+			}
+			let mismatched = versions.values().collect::<BTreeSet<_>>().len() > 1;
+			if self.mismatched && !mismatched {
+				continue;
+			}
+
+			found = true;
+			let loaders: BTreeSet<Loader> = declared.iter().map(|(loader, _, _)| *loader).collect();
+			if loaders.is_empty() {
+				println!("{}: (Not a mod)", display_name(&jar.0));
+			} else {
+				let loaders: Vec<String> = loaders.iter().map(Loader::to_string).collect();
+				println!("{}: {}", display_name(&jar.0), loaders.join(", "));
+			}
+			if mismatched {
+				let versions: Vec<String> = versions
+					.iter()
+					.map(|(loader, version)| format!("{} {}", loader, version))
+					.collect();
+				println!("    {}", color::red(&format!("Mismatched versions: {}", versions.join(", "))));
+			}
+		}
+		if self.mismatched && !found {
+			println!("No jars with mismatched versions found!");
+		}
+
+		Ok(())
+	}
+}
+
+/// Lists the language adapters (such as Kotlin) that mods in the current folder use, and the mods that provide them
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct AdaptersCommand {}
+
+impl JarCommand for AdaptersCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		#[derive(Default)]
+		struct Adapter {
+			providers: BTreeSet<String>,
+			/// Mods with entrypoints loaded by the adapter
+			users: BTreeSet<String>,
+			/// Mods that depend on a provider of the adapter, which may only need its libraries
+			dependents: BTreeSet<String>,
+		}
+
+		let mut adapters: BTreeMap<String, Adapter> = BTreeMap::new();
+		let mut depends_on: Vec<(String, &str)> = vec![];
+		let mut provider_ids: BTreeSet<(&str, &str)> = LANGUAGE_PROVIDERS.iter().copied().collect();
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, nested_file_name, parent| {
+				if let TraversedJar::FabricJar {
+					mod_id,
+					adapters: used,
+					language_adapters,
+					depends,
+					..
+				} = nested
+				{
+					let label = match parent {
+						Some(_) => format!("{} ({} > {})", mod_id, file_name(&jar.0), nested_file_name),
+						None => format!("{} ({})", mod_id, nested_file_name),
+					};
+					for adapter in used {
+						adapters.entry(adapter.clone()).or_default().users.insert(label.clone());
+					}
+					for adapter in language_adapters.keys() {
+						adapters.entry(adapter.clone()).or_default().providers.insert(label.clone());
+						provider_ids.insert((adapter, mod_id));
+					}
+					for dependency in depends.keys() {
+						depends_on.push((label.clone(), dependency));
+					}
+				}
+			});
+		}
+		for (adapter, provider_id) in &provider_ids {
+			for (label, dependency) in &depends_on {
+				if dependency == provider_id {
+					adapters
+						.entry((*adapter).to_owned())
+						.or_default()
+						.dependents
+						.insert(label.clone());
+				}
+			}
+		}
+
+		for (name, adapter) in &adapters {
+			let providers: Vec<String> = adapter.providers.iter().cloned().collect();
+			if providers.is_empty() {
+				let known = LANGUAGE_PROVIDERS.iter().find(|(adapter, _)| adapter == name);
+				let message = match known {
+					Some((_, provider_id)) => format!("Not provided by any mod, install {}", provider_id),
+					None => "Not provided by any mod".to_owned(),
+				};
+				println!("{}: {}", color::bold(name), color::red(&message));
+			} else {
+				println!("{}: provided by {}", color::bold(name), providers.join(", "));
+			}
+			if !adapter.users.is_empty() {
+				println!(
+					"    Used by: {}",
+					adapter.users.iter().cloned().collect::<Vec<_>>().join(", ")
+				);
+			}
+			if !adapter.dependents.is_empty() {
+				println!(
+					"    Depended on by: {}",
+					adapter.dependents.iter().cloned().collect::<Vec<_>>().join(", ")
+				);
+			}
+			if adapter.users.is_empty() && adapter.dependents.is_empty() {
+				println!("    {}", color::yellow("Not used by any mod"));
+			}
+		}
+		if adapters.is_empty() {
+			println!("No mods using language adapters found!");
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,531 @@
+use crate::{file_name, hash, version, DetailLevel, JarCommand, RunContext, TraversedJar};
+use anyhow::{anyhow, Result};
+use clap::{AppSettings, Clap};
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+};
+
+const USER_AGENT: &str = concat!("comp500/mod_jar_inspector/", env!("CARGO_PKG_VERSION"));
+const MODRINTH_API: &str = "https://api.modrinth.com/v2";
+const CURSEFORGE_API: &str = "https://api.curseforge.com/v1";
+const CURSEFORGE_MINECRAFT_ID: u32 = 432;
+pub const CURSEFORGE_API_KEY_VAR: &str = "CURSEFORGE_API_KEY";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModrinthVersion {
+	pub id: String,
+	pub project_id: String,
+	pub version_number: String,
+	#[serde(default)]
+	pub game_versions: Vec<String>,
+	#[serde(default)]
+	pub loaders: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModrinthProject {
+	pub id: String,
+	pub slug: String,
+	pub title: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModrinthMatch {
+	pub project: ModrinthProject,
+	pub version: ModrinthVersion,
+}
+
+impl ModrinthMatch {
+	pub fn url(&self) -> String {
+		format!("https://modrinth.com/mod/{}", self.project.slug)
+	}
+}
+
+/// Looks up the Modrinth versions with the given SHA-1 hashes, returning the matches keyed by hash
+pub fn modrinth_lookup(hashes: &[String]) -> Result<BTreeMap<String, ModrinthMatch>> {
+	if hashes.is_empty() {
+		return Ok(BTreeMap::new());
+	}
+
+	let versions: BTreeMap<String, ModrinthVersion> = ureq::post(&format!("{}/version_files", MODRINTH_API))
+		.set("User-Agent", USER_AGENT)
+		.send_json(json!({ "hashes": hashes, "algorithm": "sha1" }))?
+		.into_json()?;
+	if versions.is_empty() {
+		return Ok(BTreeMap::new());
+	}
+
+	let project_ids: Vec<&str> = versions.values().map(|v| v.project_id.as_str()).collect();
+	let projects: Vec<ModrinthProject> = ureq::get(&format!("{}/projects", MODRINTH_API))
+		.set("User-Agent", USER_AGENT)
+		.query("ids", &serde_json::to_string(&project_ids)?)
+		.call()?
+		.into_json()?;
+	let projects: BTreeMap<String, ModrinthProject> = projects.into_iter().map(|p| (p.id.clone(), p)).collect();
+
+	Ok(versions
+		.into_iter()
+		.filter_map(|(hash, version)| {
+			let project = projects.get(&version.project_id)?.clone();
+			Some((hash, ModrinthMatch { project, version }))
+		})
+		.collect())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CurseForgeResponse<T> {
+	data: T,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeFingerprintMatches {
+	exact_matches: Vec<CurseForgeFingerprintMatch>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CurseForgeFingerprintMatch {
+	file: CurseForgeFile,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFile {
+	pub id: u32,
+	pub mod_id: u32,
+	pub display_name: String,
+	pub file_fingerprint: u32,
+	#[serde(default)]
+	pub game_versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeLinks {
+	pub website_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFileIndex {
+	pub game_version: String,
+	pub file_id: u32,
+	pub filename: String,
+	pub mod_loader: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMod {
+	pub id: u32,
+	pub name: String,
+	pub slug: String,
+	pub links: CurseForgeLinks,
+	#[serde(default)]
+	pub latest_files_indexes: Vec<CurseForgeFileIndex>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CurseForgeMatch {
+	pub project: CurseForgeMod,
+	pub file: CurseForgeFile,
+}
+
+impl CurseForgeMatch {
+	pub fn url(&self) -> String {
+		self.file_url(self.file.id)
+	}
+
+	/// The URL of another file of this project
+	pub fn file_url(&self, file_id: u32) -> String {
+		let project_url = self
+			.project
+			.links
+			.website_url
+			.clone()
+			.unwrap_or_else(|| format!("https://www.curseforge.com/minecraft/mc-mods/{}", self.project.slug));
+		format!("{}/files/{}", project_url, file_id)
+	}
+}
+
+/// Finds the latest Fabric versions compatible with the given game versions of the Modrinth projects owning each
+/// SHA-1 hash, returning them keyed by hash
+pub fn modrinth_latest(hashes: &[String], game_versions: &[String]) -> Result<BTreeMap<String, ModrinthVersion>> {
+	if hashes.is_empty() {
+		return Ok(BTreeMap::new());
+	}
+
+	Ok(ureq::post(&format!("{}/version_files/update", MODRINTH_API))
+		.set("User-Agent", USER_AGENT)
+		.send_json(json!({
+			"hashes": hashes,
+			"algorithm": "sha1",
+			"loaders": ["fabric"],
+			"game_versions": game_versions,
+		}))?
+		.into_json()?)
+}
+
+/// CurseForge's id for Fabric in `modLoader` fields
+pub const CURSEFORGE_FABRIC_LOADER: u32 = 4;
+
+impl CurseForgeMatch {
+	/// Finds the newest Fabric file of this project for any of the given game versions, if it is newer than this file
+	pub fn latest_file(&self, game_versions: &[String]) -> Option<&CurseForgeFileIndex> {
+		self.project
+			.latest_files_indexes
+			.iter()
+			.filter(|index| index.mod_loader.is_none_or(|loader| loader == CURSEFORGE_FABRIC_LOADER))
+			.filter(|index| game_versions.contains(&index.game_version))
+			.filter(|index| index.file_id > self.file.id)
+			.max_by_key(|index| index.file_id)
+	}
+}
+
+fn curseforge_api_key() -> Result<String> {
+	std::env::var(CURSEFORGE_API_KEY_VAR).map_err(|_| {
+		anyhow!(
+			"The {} environment variable must be set to use the CurseForge API",
+			CURSEFORGE_API_KEY_VAR
+		)
+	})
+}
+
+/// Looks up the CurseForge files with the given fingerprints, returning the matches keyed by fingerprint
+pub fn curseforge_lookup(fingerprints: &[u32]) -> Result<BTreeMap<u32, CurseForgeMatch>> {
+	let api_key = curseforge_api_key()?;
+	if fingerprints.is_empty() {
+		return Ok(BTreeMap::new());
+	}
+
+	let matches: CurseForgeResponse<CurseForgeFingerprintMatches> =
+		ureq::post(&format!("{}/fingerprints/{}", CURSEFORGE_API, CURSEFORGE_MINECRAFT_ID))
+			.set("User-Agent", USER_AGENT)
+			.set("x-api-key", &api_key)
+			.send_json(json!({ "fingerprints": fingerprints }))?
+			.into_json()?;
+	let files: Vec<CurseForgeFile> = matches.data.exact_matches.into_iter().map(|m| m.file).collect();
+	if files.is_empty() {
+		return Ok(BTreeMap::new());
+	}
+
+	let mod_ids: Vec<u32> = files.iter().map(|f| f.mod_id).collect();
+	let projects: CurseForgeResponse<Vec<CurseForgeMod>> = ureq::post(&format!("{}/mods", CURSEFORGE_API))
+		.set("User-Agent", USER_AGENT)
+		.set("x-api-key", &api_key)
+		.send_json(json!({ "modIds": mod_ids }))?
+		.into_json()?;
+	let projects: BTreeMap<u32, CurseForgeMod> = projects.data.into_iter().map(|p| (p.id, p)).collect();
+
+	Ok(files
+		.into_iter()
+		.filter_map(|file| {
+			let project = projects.get(&file.mod_id)?.clone();
+			Some((file.file_fingerprint, CurseForgeMatch { project, file }))
+		})
+		.collect())
+}
+
+/// Identifies jars in the current folder using mod platform APIs
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct LookupCommand {
+	/// Look up jars on Modrinth by their SHA-1 hash
+	#[clap(long)]
+	modrinth: bool,
+	/// Look up jars on CurseForge by their fingerprint, using the API key in the CURSEFORGE_API_KEY environment variable
+	#[clap(long)]
+	curseforge: bool,
+	/// Only list the jars that weren't found on any of the selected platforms
+	#[clap(long)]
+	unmatched: bool,
+}
+
+impl JarCommand for LookupCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("lookup")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		if !self.modrinth && !self.curseforge {
+			anyhow::bail!("No platform selected, use --modrinth or --curseforge to select where to look up jars");
+		}
+
+		let mut jars = vec![];
+		for jar in &processed_jars {
+			let contents = std::fs::read(&jar.0)?;
+			jars.push((
+				file_name(&jar.0),
+				hash::hash("sha1", &contents),
+				hash::curseforge_fingerprint(&contents),
+			));
+		}
+		jars.sort();
+
+		let modrinth_matches = if self.modrinth {
+			modrinth_lookup(&jars.iter().map(|jar| jar.1.clone()).collect::<Vec<_>>())?
+		} else {
+			BTreeMap::new()
+		};
+		let curseforge_matches = if self.curseforge {
+			curseforge_lookup(&jars.iter().map(|jar| jar.2).collect::<Vec<_>>())?
+		} else {
+			BTreeMap::new()
+		};
+
+		let mut unmatched_jars = 0;
+		for (file_name, hash, fingerprint) in &jars {
+			let modrinth_match = modrinth_matches.get(hash);
+			let curseforge_match = curseforge_matches.get(fingerprint);
+			if self.unmatched {
+				if modrinth_match.is_none() && curseforge_match.is_none() {
+					unmatched_jars += 1;
+					println!("{}", file_name);
+				}
+				continue;
+			}
+
+			println!("{}", file_name);
+			if self.modrinth {
+				match modrinth_match {
+					Some(found) => println!(
+						"    Modrinth: {} {} ({}) for {} - {}",
+						found.project.title,
+						found.version.version_number,
+						found.version.loaders.join(", "),
+						found.version.game_versions.join(", "),
+						found.url()
+					),
+					None => println!("    Modrinth: not found"),
+				}
+			}
+			if self.curseforge {
+				match curseforge_match {
+					Some(found) => println!(
+						"    CurseForge: {} {} for {} - {}",
+						found.project.name,
+						found.file.display_name,
+						found.file.game_versions.join(", "),
+						found.url()
+					),
+					None => println!("    CurseForge: not found"),
+				}
+			}
+		}
+		if self.unmatched && unmatched_jars == 0 {
+			println!("All jars were found!");
+		}
+
+		Ok(())
+	}
+}
+
+/// Checks mods in the current folder for newer compatible versions on mod platforms
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct OutdatedCommand {
+	/// Only look for versions supporting these Minecraft versions, instead of the ones the installed version supports
+	#[clap(long)]
+	game_version: Vec<String>,
+	/// Also check CurseForge, using the API key in the CURSEFORGE_API_KEY environment variable
+	#[clap(long)]
+	curseforge: bool,
+	/// Print the available updates as JSON
+	#[clap(long)]
+	json: bool,
+}
+
+impl JarCommand for OutdatedCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("outdated")
+	}
+
+	fn is_scripted(&self) -> bool {
+		self.json
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		struct InstalledMod<'a> {
+			file_name: &'a str,
+			mod_id: &'a str,
+			mod_version: &'a str,
+			declared_minecraft: Option<String>,
+			minecraft_range: Option<version::VersionRange>,
+			hash: String,
+			fingerprint: u32,
+		}
+
+		struct Update<'a> {
+			installed: &'a InstalledMod<'a>,
+			platform: &'static str,
+			latest_version: String,
+			url: String,
+		}
+
+		/// The game versions the platform lists for the installed version that its declared Minecraft dependency
+		/// allows, or all of them if it doesn't declare one or none of them are allowed
+		fn supported_game_versions(platform: &[String], installed: &InstalledMod) -> Vec<String> {
+			let range = match &installed.minecraft_range {
+				Some(range) => range,
+				None => return platform.to_vec(),
+			};
+			let supported: Vec<String> = platform
+				.iter()
+				.filter(|game_version| version::Version::parse(game_version).is_some_and(|v| range.contains(&v)))
+				.cloned()
+				.collect();
+			if supported.is_empty() {
+				platform.to_vec()
+			} else {
+				supported
+			}
+		}
+
+		let mut installed_mods = vec![];
+		for jar in &processed_jars {
+			if let TraversedJar::FabricJar {
+				mod_id,
+				mod_version,
+				depends,
+				..
+			} = &jar.1
+			{
+				let contents = std::fs::read(&jar.0)?;
+				installed_mods.push(InstalledMod {
+					file_name: file_name(&jar.0),
+					mod_id,
+					mod_version,
+					declared_minecraft: depends.get("minecraft").map(|ranges| ranges.join(" || ")),
+					minecraft_range: depends
+						.get("minecraft")
+						.and_then(|ranges| version::VersionRange::parse_any(ranges)),
+					hash: hash::hash("sha1", &contents),
+					fingerprint: hash::curseforge_fingerprint(&contents),
+				});
+			}
+		}
+		installed_mods.sort_by_key(|installed| (installed.mod_id, installed.file_name));
+
+		let mut updates = vec![];
+		let mut found_mods = BTreeSet::new();
+
+		let hashes: Vec<String> = installed_mods.iter().map(|installed| installed.hash.clone()).collect();
+		let modrinth_matches = modrinth_lookup(&hashes)?;
+		// The update endpoint takes one list of game versions, so look up mods supporting the same versions together
+		let mut game_version_groups: BTreeMap<Vec<String>, Vec<String>> = BTreeMap::new();
+		for installed in &installed_mods {
+			if let Some(found) = modrinth_matches.get(&installed.hash) {
+				let game_versions = if self.game_version.is_empty() {
+					supported_game_versions(&found.version.game_versions, installed)
+				} else {
+					self.game_version.clone()
+				};
+				let hashes = game_version_groups.entry(game_versions).or_default();
+				if !hashes.contains(&installed.hash) {
+					hashes.push(installed.hash.clone());
+				}
+			}
+		}
+		let mut latest_versions = BTreeMap::new();
+		for (game_versions, hashes) in &game_version_groups {
+			latest_versions.extend(modrinth_latest(hashes, game_versions)?);
+		}
+		for installed in &installed_mods {
+			if let (Some(found), Some(latest)) = (modrinth_matches.get(&installed.hash), latest_versions.get(&installed.hash)) {
+				found_mods.insert(installed.file_name);
+				if latest.id != found.version.id {
+					updates.push(Update {
+						installed,
+						platform: "Modrinth",
+						latest_version: latest.version_number.clone(),
+						url: format!("{}/version/{}", found.url(), latest.id),
+					});
+				}
+			}
+		}
+
+		if self.curseforge {
+			let fingerprints: Vec<u32> = installed_mods.iter().map(|installed| installed.fingerprint).collect();
+			let curseforge_matches = curseforge_lookup(&fingerprints)?;
+			for installed in &installed_mods {
+				if let Some(found) = curseforge_matches.get(&installed.fingerprint) {
+					found_mods.insert(installed.file_name);
+					let game_versions = if self.game_version.is_empty() {
+						supported_game_versions(&found.file.game_versions, installed)
+					} else {
+						self.game_version.clone()
+					};
+					if let Some(latest) = found.latest_file(&game_versions) {
+						updates.push(Update {
+							installed,
+							platform: "CurseForge",
+							latest_version: latest.filename.clone(),
+							url: found.file_url(latest.file_id),
+						});
+					}
+				}
+			}
+		}
+
+		if self.json {
+			let output: Vec<_> = updates
+				.iter()
+				.map(|update| {
+					serde_json::json!({
+						"file": update.installed.file_name,
+						"mod_id": update.installed.mod_id,
+						"installed_version": update.installed.mod_version,
+						"declared_minecraft": update.installed.declared_minecraft,
+						"platform": update.platform,
+						"latest_version": update.latest_version,
+						"url": update.url,
+					})
+				})
+				.collect();
+			println!("{}", serde_json::to_string_pretty(&output)?);
+		} else {
+			let mut last_file = None;
+			for update in &updates {
+				if last_file != Some(update.installed.file_name) {
+					last_file = Some(update.installed.file_name);
+					match &update.installed.declared_minecraft {
+						Some(minecraft) => println!(
+							"{} ({}, requires minecraft {})",
+							update.installed.mod_id, update.installed.file_name, minecraft
+						),
+						None => println!("{} ({})", update.installed.mod_id, update.installed.file_name),
+					}
+				}
+				println!(
+					"    {}: {} -> {} - {}",
+					update.platform, update.installed.mod_version, update.latest_version, update.url
+				);
+			}
+			let outdated_mods: BTreeSet<&str> = updates.iter().map(|update| update.installed.file_name).collect();
+			println!(
+				"{} of {} mods found on mod platforms have updates available",
+				outdated_mods.len(),
+				found_mods.len()
+			);
+			if found_mods.len() < installed_mods.len() {
+				println!(
+					"{} mods weren't found on any platform",
+					installed_mods.len() - found_mods.len()
+				);
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -1,29 +1,84 @@
+mod access_widener;
+mod bundle;
+mod classes;
+mod classfile;
+mod color;
+mod config;
+mod content;
+mod deps;
+mod diff;
+mod duplicates;
+mod environment;
+mod extract;
+mod fabric_api;
+mod flatten;
+mod hash;
+mod icons;
+mod jar_in_jar;
+mod json;
+mod lang;
+mod licenses;
+mod lint;
+mod list;
+mod loaders;
+mod lookup;
+mod mixin;
+mod mod_menu;
+mod pack;
+mod query;
+mod report;
+mod requirements;
+mod scan;
+mod serve;
+mod snapshot;
+mod stats;
+mod strings;
+mod touches;
+mod transformers;
+mod tui;
+mod verify;
+mod version;
+mod watch;
+
 use anyhow::Result;
 use clap::{crate_version, AppSettings, Clap};
 use enum_map::{enum_map, Enum, EnumMap};
+use indicatif::{ProgressBar, ProgressStyle};
+use memmap2::Mmap;
 use rayon::prelude::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 use std::{
+	cmp::Ordering,
 	collections::{BTreeMap, BTreeSet},
-	ffi::OsStr,
+	env,
+	ffi::{OsStr, OsString},
+	fmt,
 	fs::File,
-	io::{BufReader, Cursor, Read, Seek},
-	path::PathBuf,
+	io::{self, BufReader, Cursor, IsTerminal, Read, Seek, SeekFrom},
+	path::{Path, PathBuf},
+	process,
+	time::{Duration, Instant},
 };
 use zip::ZipArchive;
 
-#[derive(Debug, Clone, Deserialize, Enum, Copy)]
+#[derive(Debug, Clone, Deserialize, Serialize, Enum, Copy, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 enum Environment {
 	#[serde(rename = "*")]
+	#[default]
 	Both,
 	Client,
 	Server,
 }
 
-impl Default for Environment {
-	fn default() -> Self {
-		Environment::Both
+impl fmt::Display for Environment {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Environment::Both => write!(f, "*"),
+			Environment::Client => write!(f, "client"),
+			Environment::Server => write!(f, "server"),
+		}
 	}
 }
 
@@ -43,6 +98,182 @@ enum MixinConfigListEntry {
 	},
 }
 
+impl MixinConfigListEntry {
+	fn config(&self) -> &str {
+		match self {
+			MixinConfigListEntry::Name(name) => name,
+			MixinConfigListEntry::WithEnvironment { config, .. } => config,
+		}
+	}
+}
+
+/// An entrypoint class (or `class::member` reference), optionally with the language adapter that loads it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum EntrypointEntry {
+	Name(String),
+	WithAdapter { adapter: Option<String>, value: String },
+}
+
+impl EntrypointEntry {
+	fn value(&self) -> &str {
+		match self {
+			EntrypointEntry::Name(value) => value,
+			EntrypointEntry::WithAdapter { value, .. } => value,
+		}
+	}
+
+	/// The language adapter that loads the entrypoint, if it isn't the default Java one
+	fn adapter(&self) -> Option<&str> {
+		match self {
+			EntrypointEntry::WithAdapter {
+				adapter: Some(adapter), ..
+			} if adapter != "default" => Some(adapter),
+			_ => None,
+		}
+	}
+}
+
+/// A field that can be either a single string or an array of strings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum StringOrList {
+	String(String),
+	List(Vec<String>),
+}
+
+impl From<StringOrList> for Vec<String> {
+	fn from(value: StringOrList) -> Self {
+		match value {
+			StringOrList::String(value) => vec![value],
+			StringOrList::List(values) => values,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", untagged)]
+enum Person {
+	Name(String),
+	WithContact {
+		name: String,
+		#[serde(default)]
+		contact: BTreeMap<String, String>,
+	},
+}
+
+impl Person {
+	/// Formats the person's name along with their email address or homepage, if they have one
+	fn describe(&self) -> String {
+		match self {
+			Person::Name(name) => name.clone(),
+			Person::WithContact { name, contact } => {
+				if let Some(email) = contact.get("email") {
+					format!("{} <{}>", name, email)
+				} else if let Some(homepage) = contact.get("homepage") {
+					format!("{} ({})", name, homepage)
+				} else {
+					name.clone()
+				}
+			}
+		}
+	}
+}
+
+/// The icon of a mod, either a single path or a map of sizes to paths
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Icon {
+	Path(String),
+	Sizes(BTreeMap<String, String>),
+}
+
+impl Icon {
+	/// Path to the icon, or the largest icon if there are multiple sizes
+	fn largest(&self) -> Option<&str> {
+		match self {
+			Icon::Path(path) => Some(path),
+			Icon::Sizes(sizes) => sizes
+				.iter()
+				.max_by_key(|(size, _)| size.parse::<u32>().unwrap_or(0))
+				.map(|(_, path)| path.as_str()),
+		}
+	}
+}
+
+/// The `modmenu` block of the `custom` field, which sets how Mod Menu shows a mod
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ModMenuJson {
+	#[serde(default)]
+	badges: Vec<String>,
+	parent: Option<ModMenuParent>,
+	#[serde(default)]
+	links: BTreeMap<String, String>,
+	update_checker: Option<bool>,
+}
+
+/// The mod that Mod Menu shows a mod under, either the id of another mod or a placeholder mod that isn't installed
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum ModMenuParent {
+	Id(String),
+	Placeholder { id: String, name: Option<String> },
+}
+
+/// How Mod Menu shows a mod
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ModMenuInfo {
+	/// Badges such as `library`, `client` and `deprecated`
+	badges: Vec<String>,
+	parent: Option<String>,
+	/// Name of the placeholder parent, for parents that don't have to be installed
+	parent_name: Option<String>,
+	links: BTreeMap<String, String>,
+	/// Whether Mod Menu checks for updates of the mod, if set
+	update_checker: Option<bool>,
+}
+
+impl ModMenuInfo {
+	fn from_custom(custom: &BTreeMap<String, serde_json::Value>) -> ModMenuInfo {
+		let mod_menu: ModMenuJson = custom
+			.get("modmenu")
+			.and_then(|value| serde_json::from_value(value.clone()).ok())
+			.unwrap_or_default();
+		let (mut parent, parent_name) = match mod_menu.parent {
+			Some(ModMenuParent::Id(id)) => (Some(id), None),
+			Some(ModMenuParent::Placeholder { id, name }) => (Some(id), name),
+			None => (None, None),
+		};
+		let mut badges = mod_menu.badges;
+		// Mod Menu 1.x used separate keys for each setting
+		if custom.get("modmenu:api").and_then(serde_json::Value::as_bool) == Some(true) {
+			badges.push("library".to_owned());
+		}
+		if custom.get("modmenu:clientsideOnly").and_then(serde_json::Value::as_bool) == Some(true) {
+			badges.push("client".to_owned());
+		}
+		if parent.is_none() {
+			parent = custom
+				.get("modmenu:parent")
+				.and_then(serde_json::Value::as_str)
+				.map(str::to_owned);
+		}
+		badges.sort();
+		badges.dedup();
+		ModMenuInfo {
+			badges,
+			parent,
+			parent_name,
+			links: mod_menu.links,
+			update_checker: mod_menu.update_checker,
+		}
+	}
+
+	fn is_library(&self) -> bool {
+		self.badges.iter().any(|badge| badge == "library")
+	}
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct FabricModJson {
@@ -55,13 +286,159 @@ struct FabricModJson {
 	jars: Vec<JarInJarListEntry>,
 	#[serde(default)]
 	mixins: Vec<MixinConfigListEntry>,
-	access_widener: Option<String>
+	#[serde(default)]
+	entrypoints: BTreeMap<String, Vec<EntrypointEntry>>,
+	#[serde(default)]
+	language_adapters: BTreeMap<String, String>,
+	access_widener: Option<String>,
+	#[serde(default)]
+	depends: BTreeMap<String, StringOrList>,
+	#[serde(default)]
+	recommends: BTreeMap<String, StringOrList>,
+	#[serde(default)]
+	breaks: BTreeMap<String, StringOrList>,
+	#[serde(default)]
+	provides: Vec<String>,
+	license: Option<StringOrList>,
+	#[serde(default)]
+	authors: Vec<Person>,
+	#[serde(default)]
+	contributors: Vec<Person>,
+	#[serde(default)]
+	contact: BTreeMap<String, String>,
+	icon: Option<Icon>,
+	#[serde(default)]
+	custom: BTreeMap<String, serde_json::Value>,
+	#[serde(default = "current_schema_version")]
+	schema_version: u64,
+}
+
+fn current_schema_version() -> u64 {
+	1
+}
+
+impl FabricModJson {
+	/// Reads a fabric.mod.json of either schema version. Fabric Loader treats files without a `schemaVersion` as
+	/// version 0, but most of them are written for version 1, so they are only read as version 0 if that fails.
+	fn from_value(value: serde_json::Value) -> serde_json::Result<FabricModJson> {
+		match value.get("schemaVersion").and_then(serde_json::Value::as_u64) {
+			Some(0) => Ok(serde_json::from_value::<FabricModJsonV0>(value)?.into()),
+			Some(_) => serde_json::from_value(value),
+			None => serde_json::from_value(value.clone()).or_else(|err| {
+				serde_json::from_value::<FabricModJsonV0>(value)
+					.map(Into::into)
+					.map_err(|_| err)
+			}),
+		}
+	}
+}
+
+/// A fabric.mod.json in the schema version 0 format of the earliest Fabric mods, which has `requires` instead of
+/// `depends` and a `mixins` object with the configs for each side
+#[derive(Debug, Clone, Deserialize)]
+struct FabricModJsonV0 {
+	id: String,
+	version: String,
+	name: Option<String>,
+	/// `universal`, `client` or `server`
+	side: Option<String>,
+	initializer: Option<String>,
+	#[serde(default)]
+	initializers: Vec<String>,
+	#[serde(default)]
+	mixins: MixinsV0,
+	#[serde(default)]
+	requires: BTreeMap<String, StringOrList>,
+	#[serde(default)]
+	recommends: BTreeMap<String, StringOrList>,
+	#[serde(default)]
+	conflicts: BTreeMap<String, StringOrList>,
+	license: Option<StringOrList>,
+	#[serde(default)]
+	authors: Vec<Person>,
+	#[serde(default)]
+	contributors: Vec<Person>,
+	links: Option<LinksV0>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct MixinsV0 {
+	client: Option<StringOrList>,
+	common: Option<StringOrList>,
+	server: Option<StringOrList>,
+}
+
+/// Either the homepage, or the `homepage`, `issues` and `sources` URLs
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum LinksV0 {
+	Homepage(String),
+	Links(BTreeMap<String, String>),
+}
+
+impl From<FabricModJsonV0> for FabricModJson {
+	fn from(v0: FabricModJsonV0) -> Self {
+		let mut mixins = vec![];
+		for (configs, environment) in [
+			(v0.mixins.common, None),
+			(v0.mixins.client, Some(Environment::Client)),
+			(v0.mixins.server, Some(Environment::Server)),
+		] {
+			for config in configs.map(Vec::from).unwrap_or_default() {
+				mixins.push(MixinConfigListEntry::WithEnvironment { config, environment });
+			}
+		}
+		let initializers: Vec<EntrypointEntry> = v0
+			.initializer
+			.into_iter()
+			.chain(v0.initializers)
+			.map(EntrypointEntry::Name)
+			.collect();
+		FabricModJson {
+			id: v0.id,
+			version: v0.version,
+			name: v0.name,
+			environment: match v0.side.as_deref() {
+				Some("client") => Environment::Client,
+				Some("server") => Environment::Server,
+				_ => Environment::Both,
+			},
+			jars: vec![],
+			mixins,
+			entrypoints: if initializers.is_empty() {
+				BTreeMap::new()
+			} else {
+				BTreeMap::from([("main".to_owned(), initializers)])
+			},
+			language_adapters: BTreeMap::new(),
+			access_widener: None,
+			depends: v0.requires,
+			recommends: v0.recommends,
+			breaks: v0.conflicts,
+			provides: vec![],
+			license: v0.license,
+			authors: v0.authors,
+			contributors: v0.contributors,
+			contact: match v0.links {
+				Some(LinksV0::Homepage(homepage)) => BTreeMap::from([("homepage".to_owned(), homepage)]),
+				Some(LinksV0::Links(links)) => links,
+				None => BTreeMap::new(),
+			},
+			icon: None,
+			custom: BTreeMap::new(),
+			schema_version: 0,
+		}
+	}
 }
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct MixinConfigJson {
 	plugin: Option<String>,
+	refmap: Option<String>,
+	package: Option<String>,
+	compatibility_level: Option<String>,
+	min_version: Option<String>,
 	#[serde(default)]
 	mixins: Vec<String>,
 	#[serde(default)]
@@ -70,53 +447,527 @@ struct MixinConfigJson {
 	server: Vec<String>,
 }
 
-#[derive(Debug)]
+/// The settings of a mixin config that determine where and with which versions of Mixin its mixins can be applied
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct MixinConfigInfo {
+	/// Path of the config in the jar
+	file: String,
+	package: Option<String>,
+	/// The Java version the mixins need, such as `JAVA_17`
+	compatibility_level: Option<String>,
+	/// The lowest version of Mixin that can apply the config
+	min_version: Option<String>,
+	/// The plugin and mixin classes that the config refers to, but that aren't in the jar
+	missing_classes: Vec<String>,
+}
+
+impl MixinConfigInfo {
+	fn describe(&self) -> String {
+		let mut details = vec![];
+		details.extend(self.package.clone());
+		details.extend(self.compatibility_level.clone());
+		details.extend(self.min_version.as_ref().map(|min_version| format!("Mixin {}", min_version)));
+		if details.is_empty() {
+			self.file.clone()
+		} else {
+			format!("{} ({})", self.file, details.join(", "))
+		}
+	}
+}
+
+/// An entry of an old Forge mod's `mcmod.info`
+#[derive(Debug, Clone, Deserialize)]
+struct McmodInfoEntry {
+	modid: String,
+	name: Option<String>,
+	version: Option<String>,
+}
+
+/// `mcmod.info` is usually a JSON array of mods, but can also be an object with the mods in `modList`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum McmodInfo {
+	List(Vec<McmodInfoEntry>),
+	WithModList {
+		#[serde(rename = "modList")]
+		mod_list: Vec<McmodInfoEntry>,
+	},
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct QuiltMetadata {
+	name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct QuiltLoaderJson {
+	id: String,
+	version: String,
+	#[serde(default)]
+	metadata: QuiltMetadata,
+}
+
+/// The parts of `quilt.mod.json` that identify the mod
+#[derive(Debug, Clone, Deserialize)]
+struct QuiltModJson {
+	quilt_loader: QuiltLoaderJson,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlEntry {
+	mod_id: String,
+	version: Option<String>,
+	display_name: Option<String>,
+}
+
+/// The parts of Forge's `META-INF/mods.toml` (or NeoForge's `META-INF/neoforge.mods.toml`) that identify the mods
+#[derive(Debug, Clone, Deserialize)]
+struct ModsToml {
+	#[serde(default)]
+	mods: Vec<ModsTomlEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Loader {
+	Fabric,
+	Quilt,
+	Forge,
+	NeoForge,
+}
+
+impl fmt::Display for Loader {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Loader::Fabric => write!(f, "Fabric"),
+			Loader::Quilt => write!(f, "Quilt"),
+			Loader::Forge => write!(f, "Forge"),
+			Loader::NeoForge => write!(f, "NeoForge"),
+		}
+	}
+}
+
+/// A mod declared in the metadata of a loader other than Fabric
+#[derive(Debug, Serialize, Deserialize)]
+struct LoaderMod {
+	loader: Loader,
+	mod_id: String,
+	mod_name: Option<String>,
+	mod_version: Option<String>,
+}
+
+/// Joins the ids of the mods in a jar for another loader, for printing in place of a Fabric mod id
+fn loader_mod_ids(mods: &[LoaderMod]) -> String {
+	let ids: BTreeSet<&str> = mods.iter().map(|loader_mod| loader_mod.mod_id.as_str()).collect();
+	ids.into_iter().collect::<Vec<_>>().join(", ")
+}
+
+/// Joins the loaders that the mods in a jar are declared for
+fn loader_names(mods: &[LoaderMod]) -> String {
+	let loaders: BTreeSet<Loader> = mods.iter().map(|loader_mod| loader_mod.loader).collect();
+	loaders.iter().map(Loader::to_string).collect::<Vec<_>>().join(", ")
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 enum TraversedJar {
 	NonMod,
+	/// A jar for loaders other than Fabric, such as Quilt or Forge (including old versions that use `mcmod.info`)
+	OtherLoaderJar {
+		mods: Vec<LoaderMod>,
+		/// Contents of `META-INF/accesstransformer.cfg`, Forge's equivalent of an access widener
+		access_transformer_contents: Option<String>,
+		/// Hex-encoded SHA-1 of the jar file
+		hash: String,
+		/// Size of the jar file in bytes
+		size: u64,
+	},
 	FabricJar {
 		mod_name: Option<String>,
 		mod_id: String,
 		mod_version: String,
+		/// Hex-encoded SHA-1 of the jar file, used to recognise identical nested copies
+		hash: String,
+		/// Size of the jar file in bytes
+		size: u64,
 		environment: Environment,
 		mixins: EnumMap<Environment, Vec<String>>,
 		mixin_config_plugins: Vec<String>,
+		mixin_configs: Vec<MixinConfigInfo>,
 		contained_jars: BTreeMap<String, TraversedJar>,
 		access_widener_contents: Option<String>,
+		/// Contents of `META-INF/accesstransformer.cfg`, for jars that also support Forge
+		access_transformer_contents: Option<String>,
+		/// Version ranges of required mods, any of which may match
+		depends: BTreeMap<String, Vec<String>>,
+		/// Version ranges of mods that should be installed alongside this mod, but aren't required
+		recommends: BTreeMap<String, Vec<String>>,
+		/// Version ranges of mods that this mod doesn't work with
+		breaks: BTreeMap<String, Vec<String>>,
+		/// Ids of other mods that this mod can be used in place of
+		provides: Vec<String>,
+		licenses: Vec<String>,
+		/// Paths of LICENSE files in the root or META-INF folder of the jar
+		license_files: Vec<String>,
+		authors: Vec<String>,
+		contributors: Vec<String>,
+		/// Contact information such as the homepage, sources and issue tracker URLs
+		contact: BTreeMap<String, String>,
+		/// Path to the icon, or the largest icon if multiple sizes are declared
+		icon: Option<String>,
+		/// Highest class file major version of the classes in this jar, excluding nested jars
+		class_version: Option<u16>,
+		/// Mods declared in metadata for other loaders, for jars that also support Quilt or Forge
+		other_loaders: Vec<LoaderMod>,
+		/// Language adapters (such as `kotlin`) that load the mod's entrypoints, other than the default Java one
+		adapters: BTreeSet<String>,
+		/// Language adapters that the mod provides, with the classes implementing them
+		language_adapters: BTreeMap<String, String>,
+		mod_menu: ModMenuInfo,
+		/// Whether the fabric.mod.json is malformed, and could only be parsed after removing a byte order mark, comments
+		/// or trailing commas
+		lenient_json: bool,
+		/// The `schemaVersion` of the fabric.mod.json: 1, or 0 for the oldest mods
+		#[serde(default = "current_schema_version")]
+		schema_version: u64,
 	},
 }
 
 fn read_mod_json<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<FabricModJson> {
-	Ok(serde_json::from_reader(zip.by_name("fabric.mod.json")?)?)
+	Ok(read_mod_json_lenient(zip)?.0)
+}
+
+/// Reads the fabric.mod.json of a jar, along with whether it could only be parsed after removing a byte order mark,
+/// comments or trailing commas
+fn read_mod_json_lenient<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<(FabricModJson, bool)> {
+	let mut contents = String::new();
+	zip.by_name("fabric.mod.json")?.read_to_string(&mut contents)?;
+	let (value, lenient) = json::from_str_lenient(&contents)?;
+	Ok((FabricModJson::from_value(value)?, lenient))
+}
+
+fn read_mcmod_info<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<Vec<McmodInfoEntry>> {
+	let mcmod_info: McmodInfo = serde_json::from_reader(zip.by_name("mcmod.info")?)?;
+	Ok(match mcmod_info {
+		McmodInfo::List(mods) => mods,
+		McmodInfo::WithModList { mod_list } => mod_list,
+	})
+}
+
+/// Reads the mods declared in a `mods.toml` file, filling in `${file.jarVersion}` from the jar's manifest
+fn read_mods_toml<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str, loader: Loader) -> Result<Vec<LoaderMod>> {
+	let mut contents = String::new();
+	zip.by_name(name)?.read_to_string(&mut contents)?;
+	let mods_toml: ModsToml = toml::from_str(&contents)?;
+
+	let mut manifest = vec![];
+	let jar_version = match zip.by_name("META-INF/MANIFEST.MF") {
+		Ok(mut file) => {
+			file.read_to_end(&mut manifest)?;
+			verify::main_attributes(&manifest).remove("Implementation-Version")
+		}
+		Err(_) => None,
+	};
+
+	Ok(mods_toml
+		.mods
+		.into_iter()
+		.map(|entry| LoaderMod {
+			loader,
+			mod_id: entry.mod_id,
+			mod_name: entry.display_name,
+			mod_version: match entry.version.as_deref() {
+				Some("${file.jarVersion}") => jar_version.clone().or(entry.version),
+				_ => entry.version,
+			},
+		})
+		.collect())
+}
+
+/// Reads a Forge access transformer, which is only needed at the full detail level
+fn read_access_transformer<R: Read + Seek>(zip: &mut ZipArchive<R>, detail: DetailLevel) -> Option<String> {
+	if detail != DetailLevel::Full {
+		return None;
+	}
+	let mut contents = String::new();
+	zip.by_name("META-INF/accesstransformer.cfg")
+		.ok()?
+		.read_to_string(&mut contents)
+		.ok()?;
+	Some(contents)
+}
+
+fn read_quilt_mod_json<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<QuiltModJson> {
+	Ok(serde_json::from_reader(zip.by_name("quilt.mod.json")?)?)
+}
+
+/// Reads the mods declared in the metadata of every loader other than Fabric that a jar has metadata for
+fn read_other_loaders<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Vec<LoaderMod> {
+	let mut mods = vec![];
+	if let Ok(quilt_mod_json) = read_quilt_mod_json(zip) {
+		let quilt_loader = quilt_mod_json.quilt_loader;
+		mods.push(LoaderMod {
+			loader: Loader::Quilt,
+			mod_id: quilt_loader.id,
+			mod_name: quilt_loader.metadata.name,
+			mod_version: Some(quilt_loader.version),
+		});
+	}
+	mods.extend(read_mods_toml(zip, "META-INF/mods.toml", Loader::Forge).unwrap_or_default());
+	mods.extend(read_mods_toml(zip, "META-INF/neoforge.mods.toml", Loader::NeoForge).unwrap_or_default());
+	if let Ok(entries) = read_mcmod_info(zip) {
+		mods.extend(entries.into_iter().map(|entry| LoaderMod {
+			loader: Loader::Forge,
+			mod_id: entry.modid,
+			mod_name: entry.name,
+			mod_version: entry.version,
+		}));
+	}
+	mods
 }
 
 fn read_mixin_config<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> Result<MixinConfigJson> {
 	Ok(serde_json::from_reader(zip.by_name(name)?)?)
 }
 
-fn traverse<R: Read + Seek>(source: R) -> Result<TraversedJar> {
+/// Lists the plugin and mixin classes of a mixin config that don't have a class file in the jar, which Mixin fails to
+/// load at runtime
+fn missing_mixin_classes<R: Read + Seek>(zip: &mut ZipArchive<R>, config: &MixinConfigJson) -> Vec<String> {
+	let mut classes: Vec<String> = config.plugin.iter().cloned().collect();
+	if let Some(package) = &config.package {
+		classes.extend(
+			config
+				.mixins
+				.iter()
+				.chain(&config.client)
+				.chain(&config.server)
+				.map(|mixin| format!("{}.{}", package, mixin)),
+		);
+	}
+	classes
+		.into_iter()
+		.filter(|class| zip.by_name(&format!("{}.class", class.replace('.', "/"))).is_err())
+		.collect()
+}
+
+/// Lists the paths of a mod's metadata files: fabric.mod.json, its access widener, and its mixin configs and their
+/// refmaps
+fn metadata_files<R: Read + Seek>(zip: &mut ZipArchive<R>, fabric_mod_json: &FabricModJson) -> BTreeSet<String> {
+	let mut metadata_files = BTreeSet::new();
+	metadata_files.insert("fabric.mod.json".to_owned());
+	metadata_files.extend(fabric_mod_json.access_widener.clone());
+	for mixin_entry in &fabric_mod_json.mixins {
+		let config = mixin_entry.config().to_owned();
+		if let Ok(MixinConfigJson {
+			refmap: Some(refmap), ..
+		}) = read_mixin_config(zip, &config)
+		{
+			metadata_files.insert(refmap);
+		}
+		metadata_files.insert(config);
+	}
+	metadata_files
+}
+
+fn hash_source<R: Read + Seek>(source: &mut R) -> Result<String> {
+	let mut hasher = Sha1::new();
+	std::io::copy(source, &mut hasher)?;
+	source.seek(SeekFrom::Start(0))?;
+	Ok(hash::to_hex(&hasher.finalize()))
+}
+
+fn nested_jar_name(file: &str) -> String {
+	file.split('/').next_back().unwrap_or(file).to_owned()
+}
+
+/// Finds the highest major version of the class files in a jar, ignoring `module-info.class` and multi-release class
+/// files that older JVMs skip
+fn max_class_version<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<Option<u16>> {
+	let mut max_version = None;
+	for i in 0..zip.len() {
+		let mut file = zip.by_index(i)?;
+		let name = file.name();
+		if !name.ends_with(".class") || name.ends_with("module-info.class") || name.starts_with("META-INF/versions/") {
+			continue;
+		}
+		let mut header = [0u8; 8];
+		if file.read_exact(&mut header).is_err() || header[..4] != [0xCA, 0xFE, 0xBA, 0xBE] {
+			continue;
+		}
+		let major = u16::from_be_bytes([header[6], header[7]]);
+		max_version = max_version.max(Some(major));
+	}
+	Ok(max_version)
+}
+
+/// Maps a class file major version to the Java release that introduced it (e.g. 52 to 8, 61 to 17)
+fn java_release(class_version: u16) -> String {
+	match class_version {
+		0..=48 => format!("1.{}", class_version.saturating_sub(44).max(1)),
+		_ => (class_version - 44).to_string(),
+	}
+}
+
+fn dependency_ranges(dependencies: BTreeMap<String, StringOrList>) -> BTreeMap<String, Vec<String>> {
+	dependencies.into_iter().map(|(id, ranges)| (id, ranges.into())).collect()
+}
+
+/// Limits on reading nested jars, so that hostile or corrupted jars can't exhaust memory
+#[derive(Clap, Debug, Clone, Copy)]
+struct TraversalLimits {
+	/// Maximum depth of jars nested within other jars
+	#[clap(long, global = true, default_value = "8")]
+	max_depth: usize,
+	/// Maximum size of a single nested jar, in MiB
+	#[clap(long, global = true, default_value = "256")]
+	max_nested_size: u64,
+	/// Maximum total size of the jars nested within a top-level jar, in MiB
+	#[clap(long, global = true, default_value = "1024")]
+	max_total_size: u64,
+}
+
+const MIB: u64 = 1024 * 1024;
+
+/// How much of the traversal limits a top-level jar has used so far
+struct TraversalBudget<'a> {
+	limits: &'a TraversalLimits,
+	/// Hashes of the jars containing the jar being read, outermost first
+	ancestors: Vec<String>,
+	/// Total size of the nested jars read so far
+	nested_size: u64,
+	/// Time spent on the outermost jar, filled in for `--timing`
+	timing: JarTiming,
+}
+
+/// How long reading a top-level jar took, split into its phases
+#[derive(Debug, Default, Clone, Copy)]
+struct JarTiming {
+	/// Opening the file, hashing it and reading the zip central directory
+	open: Duration,
+	/// Reading fabric.mod.json, mixin configs and the other metadata of the jar itself
+	parse: Duration,
+	/// Reading and traversing the jars nested inside it, at any depth
+	nested: Duration,
+}
+
+impl JarTiming {
+	fn total(&self) -> Duration {
+		self.open + self.parse + self.nested
+	}
+}
+
+impl<'a> TraversalBudget<'a> {
+	fn new(limits: &'a TraversalLimits) -> Self {
+		TraversalBudget {
+			limits,
+			ancestors: vec![],
+			nested_size: 0,
+			timing: JarTiming::default(),
+		}
+	}
+}
+
+/// Fails if a jar nested in the current one would be nested more deeply than `--max-depth`
+fn check_nesting_depth(name: &str, budget: &TraversalBudget) -> Result<()> {
+	if budget.ancestors.len() >= budget.limits.max_depth {
+		anyhow::bail!(
+			"{} is nested more than {} jars deep (see --max-depth)",
+			name,
+			budget.limits.max_depth
+		);
+	}
+	Ok(())
+}
+
+/// Reads a nested jar into memory, failing if it would exceed the size limits
+fn read_nested_jar<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str, budget: &mut TraversalBudget) -> Result<Vec<u8>> {
+	let max_nested_size = budget.limits.max_nested_size * MIB;
+	let remaining = (budget.limits.max_total_size * MIB).saturating_sub(budget.nested_size);
+	let limit = max_nested_size.min(remaining);
+
+	let mut contents = vec![];
+	zip.by_name(name)?.take(limit + 1).read_to_end(&mut contents)?;
+	if contents.len() as u64 > limit {
+		if limit == max_nested_size {
+			anyhow::bail!(
+				"{} is larger than the limit of {} MiB for a nested jar (see --max-nested-size)",
+				name,
+				budget.limits.max_nested_size
+			);
+		}
+		anyhow::bail!(
+			"the nested jars are larger than the limit of {} MiB in total (see --max-total-size)",
+			budget.limits.max_total_size
+		);
+	}
+	budget.nested_size += contents.len() as u64;
+	Ok(contents)
+}
+
+/// How much of each jar is read
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DetailLevel {
+	/// Only fabric.mod.json and nested jars; mixins, access wideners, class versions, hashes, license files and other
+	/// loaders' metadata in Fabric jars are left empty
+	Metadata,
+	Full,
+}
+
+/// Traverses a jar, also returning how long each phase took
+fn traverse_timed<R: Read + Seek>(source: R, detail: DetailLevel, limits: &TraversalLimits) -> (Result<TraversedJar>, JarTiming) {
+	let start = Instant::now();
+	let mut budget = TraversalBudget::new(limits);
+	let traversed = traverse_within(source, detail, &mut budget);
+	let mut timing = budget.timing;
+	timing.parse = start.elapsed().saturating_sub(timing.open + timing.nested);
+	(traversed, timing)
+}
+
+fn traverse_within<R: Read + Seek>(mut source: R, detail: DetailLevel, budget: &mut TraversalBudget) -> Result<TraversedJar> {
+	let start = Instant::now();
+	let size = source.seek(SeekFrom::End(0))?;
+	source.seek(SeekFrom::Start(0))?;
+	// Without hashes a jar that contains a copy of itself is only stopped by the depth and size limits
+	let hash = match detail {
+		DetailLevel::Full => hash_source(&mut source)?,
+		DetailLevel::Metadata => String::new(),
+	};
+	if detail == DetailLevel::Full && budget.ancestors.contains(&hash) {
+		anyhow::bail!("a nested jar contains a copy of itself");
+	}
 	let mut zip = zip::ZipArchive::new(source)?;
+	if budget.ancestors.is_empty() {
+		budget.timing.open += start.elapsed();
+	}
+
+	if let Ok((mut fabric_mod_json, lenient_json)) = read_mod_json_lenient(&mut zip) {
+		if detail == DetailLevel::Metadata {
+			fabric_mod_json.mixins.clear();
+			fabric_mod_json.access_widener = None;
+		}
 
-	if let Ok(fabric_mod_json) = read_mod_json(&mut zip) {
 		let mut contained_jars = BTreeMap::new();
 		for jar_entry in fabric_mod_json.jars {
-			let mut jar_file = BufReader::new(zip.by_name(jar_entry.file.as_str())?);
-			let mut file_contents = vec![];
-			jar_file.read_to_end(&mut file_contents)?;
-
-			contained_jars.insert(
-				jar_entry
-					.file
-					.split('/')
-					.last()
-					.map(|s| s.to_owned())
-					.unwrap_or(jar_entry.file),
-				traverse(Cursor::new(file_contents))?,
-			);
+			check_nesting_depth(&jar_entry.file, budget)?;
+			let nested_start = Instant::now();
+			let file_contents = read_nested_jar(&mut zip, &jar_entry.file, budget)?;
+
+			budget.ancestors.push(hash.clone());
+			let contained_jar = traverse_within(Cursor::new(file_contents), detail, budget);
+			budget.ancestors.pop();
+			if budget.ancestors.is_empty() {
+				budget.timing.nested += nested_start.elapsed();
+			}
+			contained_jars.insert(nested_jar_name(&jar_entry.file), contained_jar?);
 		}
 
 		let mut mixins: EnumMap<Environment, Vec<String>> = enum_map! { _ => vec![] };
 		let mut mixin_config_plugins = vec![];
+		let mut mixin_configs = vec![];
 		for mixin_entry in fabric_mod_json.mixins {
+			let config_file = mixin_entry.config().to_owned();
 			if let (env_forced, Ok(mixin_config_file)) = match mixin_entry {
 				MixinConfigListEntry::Name(name) => (None, read_mixin_config(&mut zip, name.as_str())),
 				MixinConfigListEntry::WithEnvironment {
@@ -131,6 +982,7 @@ fn traverse<R: Read + Seek>(source: R) -> Result<TraversedJar> {
 					(environment, read_mixin_config(&mut zip, config.as_str()))
 				}
 			} {
+				let missing_classes = missing_mixin_classes(&mut zip, &mixin_config_file);
 				for mixin in mixin_config_file.mixins {
 					mixins[env_forced.unwrap_or(Environment::Both)].push(mixin);
 				}
@@ -143,6 +995,13 @@ fn traverse<R: Read + Seek>(source: R) -> Result<TraversedJar> {
 				if let Some(plugin) = mixin_config_file.plugin {
 					mixin_config_plugins.push(plugin);
 				}
+				mixin_configs.push(MixinConfigInfo {
+					file: config_file,
+					package: mixin_config_file.package,
+					compatibility_level: mixin_config_file.compatibility_level,
+					min_version: mixin_config_file.min_version,
+					missing_classes,
+				});
 			}
 		}
 
@@ -154,392 +1013,1091 @@ fn traverse<R: Read + Seek>(source: R) -> Result<TraversedJar> {
 			None
 		};
 
+		let (license_files, class_version, other_loaders) = match detail {
+			DetailLevel::Full => (
+				zip.file_names()
+					.filter(|name| {
+						let file = name.strip_prefix("META-INF/").unwrap_or(name);
+						!file.contains('/') && file.to_uppercase().starts_with("LICENSE")
+					})
+					.map(|name| name.to_owned())
+					.collect(),
+				max_class_version(&mut zip)?,
+				read_other_loaders(&mut zip),
+			),
+			DetailLevel::Metadata => (vec![], None, vec![]),
+		};
+
+		let adapters = fabric_mod_json
+			.entrypoints
+			.values()
+			.flatten()
+			.filter_map(EntrypointEntry::adapter)
+			.map(str::to_owned)
+			.collect();
+
 		return Ok(TraversedJar::FabricJar {
 			mod_name: fabric_mod_json.name,
 			mod_id: fabric_mod_json.id,
 			mod_version: fabric_mod_json.version,
+			hash,
+			size,
 			environment: fabric_mod_json.environment,
 			mixins,
 			mixin_config_plugins,
+			mixin_configs,
 			contained_jars,
-			access_widener_contents
+			access_widener_contents,
+			access_transformer_contents: read_access_transformer(&mut zip, detail),
+			depends: dependency_ranges(fabric_mod_json.depends),
+			recommends: dependency_ranges(fabric_mod_json.recommends),
+			breaks: dependency_ranges(fabric_mod_json.breaks),
+			provides: fabric_mod_json.provides,
+			licenses: fabric_mod_json.license.map(Into::into).unwrap_or_default(),
+			license_files,
+			authors: fabric_mod_json.authors.iter().map(Person::describe).collect(),
+			contributors: fabric_mod_json.contributors.iter().map(Person::describe).collect(),
+			contact: fabric_mod_json.contact,
+			icon: fabric_mod_json
+				.icon
+				.as_ref()
+				.and_then(Icon::largest)
+				.map(|path| path.to_owned()),
+			class_version,
+			other_loaders,
+			adapters,
+			language_adapters: fabric_mod_json.language_adapters,
+			mod_menu: ModMenuInfo::from_custom(&fabric_mod_json.custom),
+			lenient_json,
+			schema_version: fabric_mod_json.schema_version,
 		});
 	}
 
-	Ok(TraversedJar::NonMod)
-}
+	let mods = read_other_loaders(&mut zip);
+	if !mods.is_empty() {
+		return Ok(TraversedJar::OtherLoaderJar {
+			mods,
+			access_transformer_contents: read_access_transformer(&mut zip, detail),
+			hash,
+			size,
+		});
+	}
 
-#[derive(Clap, Debug)]
-#[clap(version = crate_version!(), setting(AppSettings::UnifiedHelpMessage), setting(AppSettings::GlobalVersion))]
-struct Opts {
-	#[clap(subcommand)]
-	subcmd: SubCommand,
+	Ok(TraversedJar::NonMod)
 }
 
-#[derive(Clap, Debug)]
-enum SubCommand {
-	Mixin(MixinCommand),
-	#[clap(alias = "jij")]
-	JarInJar(JarInJarCommand),
-	#[clap(alias = "aw")]
-	AccessWidener(AccessWidenerCommand),
-	Raw(RawCommand),
+/// Options for how the jars in a folder are read
+#[derive(Clap, Debug, Clone)]
+struct ScanOptions {
+	/// Also read jars that launchers have disabled by renaming them, e.g. to .jar.disabled or .jar.old
+	#[clap(long, global = true)]
+	include_disabled: bool,
+	/// Number of jars to read at the same time; defaults to the number of CPU cores. Lower values can help on
+	/// spinning disks, higher values on network drives.
+	#[clap(long, global = true)]
+	threads: Option<usize>,
+	/// Memory-map jars instead of reading them through a buffer, which is usually faster on local disks
+	#[clap(long, global = true)]
+	mmap: bool,
+	/// Only read jars whose file names match one of these glob patterns, e.g. "*fabric*"
+	#[clap(long, global = true, use_delimiter = true)]
+	include: Vec<glob::Pattern>,
+	/// Skip jars whose file names match one of these glob patterns, e.g. "*-sources.jar,*-dev.jar"
+	#[clap(long, global = true, use_delimiter = true)]
+	exclude: Vec<glob::Pattern>,
+	/// Print how long each jar took to open, parse and read the jars nested inside it, for the slowest jars, along
+	/// with the total time taken to read the folder
+	#[clap(long, global = true)]
+	timing: bool,
+	/// Number of jars to show with --timing
+	#[clap(long, global = true, default_value = "10")]
+	timing_count: usize,
+	#[clap(flatten)]
+	limits: TraversalLimits,
 }
 
-/// Lists mixins in mods in the current folder
-#[derive(Clap, Debug)]
-#[clap(setting(AppSettings::UnifiedHelpMessage))]
-struct MixinCommand {
-	/// Filter the list of mixins using this search string
-	#[clap(long)]
-	filter: Option<String>,
+struct ScannedDir {
+	jars: Vec<(PathBuf, TraversedJar)>,
+	/// Paths of jars that couldn't be read
+	failed: Vec<PathBuf>,
 }
 
-/// Displays the Jar in Jar tree for the current folder
-#[derive(Clap, Debug)]
-#[clap(setting(AppSettings::UnifiedHelpMessage))]
-struct JarInJarCommand {
-	/// Display the reverse tree, only showing jars which are contained by other jars
-	#[clap(short, long)]
-	reverse: bool,
-	/// Filter the list of top-level mods (by mod id) using this search string
-	#[clap(long)]
-	filter: Option<String>,
+/// Traverses a single top-level jar, reading it with the I/O strategy selected in `scan`
+fn read_jar(path: &Path, detail: DetailLevel, scan: &ScanOptions) -> (Result<TraversedJar>, JarTiming) {
+	let start = Instant::now();
+	let (traversed, mut timing) = match File::open(path) {
+		Err(err) => (Err(err.into()), JarTiming::default()),
+		// SAFETY: the jar is only read while it is mapped; if another program modifies it at the same time, the
+		// results may be garbage, just as if it had been modified while being read with BufReader
+		Ok(file) if scan.mmap => match unsafe { Mmap::map(&file) } {
+			Ok(mmap) => traverse_timed(Cursor::new(&mmap[..]), detail, &scan.limits),
+			Err(err) => (Err(err.into()), JarTiming::default()),
+		},
+		Ok(file) => traverse_timed(BufReader::new(file), detail, &scan.limits),
+	};
+	// Opening (and mapping) the file counts as part of opening the jar
+	timing.open = start.elapsed().saturating_sub(timing.parse + timing.nested);
+	(traversed, timing)
 }
 
-/// Prints access widener files in mods in the current folder
-#[derive(Clap, Debug)]
-#[clap(setting(AppSettings::UnifiedHelpMessage))]
-struct AccessWidenerCommand {
-	/// Filter the files using this search string
-	#[clap(long)]
-	filter: Option<String>,
+/// Prints the slowest jars read by [scan_dir] for `--timing`
+fn print_timings(timings: &mut [(PathBuf, JarTiming)], count: usize, wall_time: Duration) {
+	timings.sort_by_key(|(_, timing)| std::cmp::Reverse(timing.total()));
+	eprintln!("Read {} jars in {:.2?}", timings.len(), wall_time);
+	if timings.is_empty() {
+		return;
+	}
+	eprintln!("Slowest jars:");
+	let width = timings
+		.iter()
+		.take(count)
+		.map(|(path, _)| file_name(path).len())
+		.max()
+		.unwrap_or(0);
+	for (path, timing) in timings.iter().take(count) {
+		eprintln!(
+			"    {:width$}  {:>9.2?}  (open {:.2?}, parse {:.2?}, nested jars {:.2?})",
+			file_name(path),
+			timing.total(),
+			timing.open,
+			timing.parse,
+			timing.nested,
+			width = width
+		);
+	}
 }
 
-/// Prints raw traversal output
-#[derive(Clap, Debug)]
-#[clap(setting(AppSettings::UnifiedHelpMessage))]
-struct RawCommand {}
-
-fn main() -> Result<()> {
-	let opts: Opts = Opts::parse();
-
-	println!("Reading mods in the current folder...");
-
-	let jar_list: Vec<_> = std::fs::read_dir(".")?
-		.into_iter()
+/// Traverses every jar in the given folder (including disabled jars if requested), sorted by path. Jars that can't be
+/// read are reported on stderr.
+fn scan_dir(dir: &Path, detail: DetailLevel, scan: &ScanOptions, show_progress: bool) -> Result<ScannedDir> {
+	let jar_list: Vec<_> = std::fs::read_dir(dir)?
 		.filter_map(Result::ok)
-		.filter(|f| f.path().is_file())
+		.map(|entry| entry.path())
+		.filter(|path| path.is_file())
+		.filter(|path| path.extension().and_then(OsStr::to_str) == Some("jar") || (scan.include_disabled && is_disabled(path)))
+		.filter(|path| scan.include.is_empty() || scan.include.iter().any(|pattern| pattern.matches(file_name(path))))
+		.filter(|path| !scan.exclude.iter().any(|pattern| pattern.matches(file_name(path))))
 		.collect();
 
-	let processed_jars: Vec<_> = jar_list
+	// The progress bar is drawn on stderr, so it can still be shown when stdout is piped
+	let progress = if show_progress && io::stderr().is_terminal() {
+		ProgressBar::new(jar_list.len() as u64)
+	} else {
+		ProgressBar::hidden()
+	};
+	progress.set_style(ProgressStyle::with_template("{bar:40} {pos}/{len} {wide_msg}").expect("progress bar template is valid"));
+
+	let start = Instant::now();
+	let results: Vec<_> = jar_list
 		.par_iter()
-		.filter(|entry| entry.path().extension().and_then(OsStr::to_str) == Some("jar"))
-		.map::<_, Result<(PathBuf, TraversedJar)>>(|entry| {
-			let file = BufReader::new(File::open(entry.path())?);
-			Ok((entry.path(), traverse(file)?))
+		.map(|path| {
+			progress.set_message(file_name(path).to_owned());
+			let (traversed, timing) = read_jar(path, detail, scan);
+			progress.inc(1);
+			(path.clone(), traversed, timing)
 		})
-		.map(|entry| entry.unwrap())
 		.collect();
+	let wall_time = start.elapsed();
+	progress.finish_and_clear();
 
-	match opts.subcmd {
-		SubCommand::Mixin(mixin_cmd) => {
-			struct FabricJar {
-				file_names: BTreeSet<String>,
-				mixins: EnumMap<Environment, BTreeSet<String>>,
+	let mut processed_jars = vec![];
+	let mut failed_jars = vec![];
+	let mut timings = vec![];
+	for (path, traversed, timing) in results {
+		if scan.timing {
+			timings.push((path.clone(), timing));
+		}
+		match traversed {
+			Ok(jar) => processed_jars.push((path, jar)),
+			Err(err) => {
+				eprintln!("Failed to read {}: {}", file_name(&path), err);
+				failed_jars.push(path);
 			}
+		}
+	}
+	processed_jars.sort_by(|a, b| a.0.cmp(&b.0));
+	failed_jars.sort();
 
-			let mut collated_jars: BTreeMap<String, FabricJar> = BTreeMap::new();
-
-			fn matches(dest: &str) -> impl FnMut(&String) -> bool + '_ {
-				move |name: &String| name.to_lowercase().contains(dest)
+	let mut lenient_jars = vec![];
+	for jar in &processed_jars {
+		walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, nested_file_name, parent| {
+			if let TraversedJar::FabricJar { lenient_json: true, .. } = nested {
+				lenient_jars.push(match parent {
+					Some(_) => format!("{} > {}", file_name(&jar.0), nested_file_name),
+					None => nested_file_name.to_owned(),
+				});
 			}
+		});
+	}
+	if !lenient_jars.is_empty() {
+		eprintln!(
+			"Warning: these jars have a malformed fabric.mod.json (with a byte order mark, comments or trailing commas) and \
+			 were only read leniently: {}",
+			lenient_jars.join(", ")
+		);
+	}
+	if scan.timing {
+		print_timings(&mut timings, scan.timing_count, wall_time);
+	}
+	Ok(ScannedDir {
+		jars: processed_jars,
+		failed: failed_jars,
+	})
+}
 
-			fn recursively_collate(
-				dest: &mut BTreeMap<String, FabricJar>, jar: TraversedJar, file_name: &str, filter: Option<String>,
-			) {
-				if let TraversedJar::FabricJar {
-					mod_id,
-					contained_jars,
-					mixins,
-					..
-				} = jar
-				{
-					let collate_dest = dest.entry(mod_id).or_insert(FabricJar {
-						file_names: BTreeSet::new(),
-						mixins: enum_map! { _ => BTreeSet::new() },
-					});
+/// Calls `visit` for the given jar and every jar nested inside it, along with the file name of each jar and the id of
+/// the mod that contains it
+fn walk_jars<'a>(
+	jar: &'a TraversedJar, file_name: &'a str, parent: Option<&'a str>,
+	visit: &mut impl FnMut(&'a TraversedJar, &'a str, Option<&'a str>),
+) {
+	visit(jar, file_name, parent);
+	if let TraversedJar::FabricJar {
+		mod_id, contained_jars, ..
+	} = jar
+	{
+		for (name, contained_jar) in contained_jars {
+			walk_jars(contained_jar, name.as_str(), Some(mod_id.as_str()), visit);
+		}
+	}
+}
 
-					collate_dest.file_names.insert(file_name.to_owned());
-					if let Some(ref filter) = filter {
-						collate_dest.mixins[Environment::Both]
-							.extend((&mixins[Environment::Both]).iter().cloned().filter(matches(filter)));
-						collate_dest.mixins[Environment::Client]
-							.extend((&mixins[Environment::Client]).iter().cloned().filter(matches(filter)));
-						collate_dest.mixins[Environment::Server]
-							.extend((&mixins[Environment::Server]).iter().cloned().filter(matches(filter)));
-					} else {
-						collate_dest.mixins[Environment::Both].extend((&mixins[Environment::Both]).iter().cloned());
-						collate_dest.mixins[Environment::Client].extend((&mixins[Environment::Client]).iter().cloned());
-						collate_dest.mixins[Environment::Server].extend((&mixins[Environment::Server]).iter().cloned());
-					}
+/// Opens the given jar (with the I/O strategy selected in `scan`) and every Fabric jar nested within it (within the
+/// limits on nested jars), calling `visit` with the chain of file names leading to each jar and its archive
+fn walk_archives(
+	jar: &Path, path: &mut Vec<String>, scan: &ScanOptions,
+	visit: &mut impl FnMut(&[String], &mut ZipArchive<Cursor<&[u8]>>) -> Result<()>,
+) -> Result<()> {
+	let mut budget = TraversalBudget::new(&scan.limits);
+	walk_archive_contents(&JarContents::open(jar, scan)?, path, &mut budget, visit)
+}
 
-					for contained_jar in contained_jars {
-						recursively_collate(dest, contained_jar.1, contained_jar.0.as_str(), (&filter).to_owned());
-					}
-				}
-			}
+fn walk_archive_contents(
+	contents: &[u8], path: &mut Vec<String>, budget: &mut TraversalBudget,
+	visit: &mut impl FnMut(&[String], &mut ZipArchive<Cursor<&[u8]>>) -> Result<()>,
+) -> Result<()> {
+	let hash = hash_source(&mut Cursor::new(contents))?;
+	if budget.ancestors.contains(&hash) {
+		anyhow::bail!("a nested jar contains a copy of itself");
+	}
+	let mut zip = ZipArchive::new(Cursor::new(contents))?;
+	visit(path, &mut zip)?;
+	if let Ok(fabric_mod_json) = read_mod_json(&mut zip) {
+		for jar_entry in fabric_mod_json.jars {
+			check_nesting_depth(&jar_entry.file, budget)?;
+			let file_contents = read_nested_jar(&mut zip, &jar_entry.file, budget)?;
+			budget.ancestors.push(hash.clone());
+			path.push(nested_jar_name(&jar_entry.file));
+			let walked = walk_archive_contents(&file_contents, path, budget, visit);
+			path.pop();
+			budget.ancestors.pop();
+			walked?;
+		}
+	}
+	Ok(())
+}
 
-			let filter = (&mixin_cmd.filter).as_ref();
-			for jar in processed_jars {
-				recursively_collate(
-					&mut collated_jars,
-					jar.1,
-					jar.0
-						.file_name()
-						.map(|f| f.to_str().unwrap())
-						.unwrap_or(jar.0.to_str().unwrap()),
-					filter.map(|filter| filter.as_str().to_lowercase()),
-				);
+/// Walks the archives of every jar in parallel, calling `visit` with each archive and returning its results in order
+/// along with the label of the archive (the mod id, or the file name for jars that aren't mods) and the chain of file
+/// names leading to it, and whether any jar couldn't be read. Those jars are reported on stderr as failing to read
+/// their `contents`.
+fn walk_labelled_archives<T: Send>(
+	jars: &[(PathBuf, TraversedJar)], scan: &ScanOptions, contents: &str,
+	visit: impl Fn(&mut ZipArchive<Cursor<&[u8]>>) -> Result<T> + Sync,
+) -> (Vec<(String, String, T)>, bool) {
+	let results: Vec<Result<Vec<(String, String, T)>>> = jars
+		.par_iter()
+		.map(|jar| {
+			let mut found = vec![];
+			let mut path = vec![display_name(&jar.0)];
+			walk_archives(&jar.0, &mut path, scan, &mut |path, zip| {
+				let label = read_mod_json(zip).map_or_else(|_| path[path.len() - 1].clone(), |json| json.id);
+				found.push((label, path.join(" > "), visit(zip)?));
+				Ok(())
+			})?;
+			Ok(found)
+		})
+		.collect();
+
+	let mut walked = vec![];
+	let mut unreadable = false;
+	for (jar, result) in jars.iter().zip(results) {
+		match result {
+			Ok(found) => walked.extend(found),
+			Err(err) => {
+				eprintln!("Failed to read the {} of {}: {}", contents, file_name(&jar.0), err);
+				unreadable = true;
 			}
+		}
+	}
+	(walked, unreadable)
+}
 
-			let mut matched_jars = false;
-			for jar in &collated_jars {
-				// If there is a filter, hide jars that don't match the filter
-				if mixin_cmd.filter.is_some() && jar.1.mixins.values().all(|v| v.is_empty()) {
-					continue;
-				}
+/// The contents of a top-level jar that is read again after it has been traversed, read with the I/O strategy selected
+/// in [ScanOptions]
+enum JarContents {
+	Mapped(Mmap),
+	Read(Vec<u8>),
+}
+
+impl JarContents {
+	fn open(path: &Path, scan: &ScanOptions) -> Result<JarContents> {
+		if scan.mmap {
+			// SAFETY: see read_jar
+			Ok(JarContents::Mapped(unsafe { Mmap::map(&File::open(path)?)? }))
+		} else {
+			Ok(JarContents::Read(std::fs::read(path)?))
+		}
+	}
+
+	fn archive(&self) -> Result<ZipArchive<Cursor<&[u8]>>> {
+		Ok(ZipArchive::new(Cursor::new(&**self))?)
+	}
+}
+
+impl std::ops::Deref for JarContents {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		match self {
+			JarContents::Mapped(mmap) => mmap,
+			JarContents::Read(contents) => contents,
+		}
+	}
+}
 
-				matched_jars = true;
-				println!(
-					"{} ({})",
-					jar.0,
-					(&jar.1.file_names).iter().cloned().collect::<Vec<String>>().join(", ")
-				);
-				for mixin in jar.1.mixins[Environment::Both].iter() {
-					println!("    {}", mixin);
+/// Lists the declared environment of every mod as an indented tree, flagging nested mods whose environment differs
+/// from their parent and, if a side is given, mods that only run on the other side. Also returns the number of
+/// flagged mods.
+fn environment_report(jars: &[(PathBuf, TraversedJar)], side: Option<Environment>) -> (Vec<String>, usize) {
+	fn recurse(
+		jar: &TraversedJar, file_name: &str, parent: Option<Environment>, side: Option<Environment>, padding: usize,
+		lines: &mut Vec<String>, flagged: &mut usize,
+	) {
+		if let TraversedJar::FabricJar {
+			mod_id,
+			environment,
+			contained_jars,
+			..
+		} = jar
+		{
+			let mut line = format!("{}{} ({}): {}", "    ".repeat(padding), mod_id, file_name, environment);
+			let mut problems = vec![];
+			if let Some(parent) = parent {
+				if parent != *environment && *environment != Environment::Both {
+					problems.push(format!("differs from parent environment {}", parent));
 				}
-				if !jar.1.mixins[Environment::Client].is_empty() {
-					println!("Client:");
-					for mixin in jar.1.mixins[Environment::Client].iter() {
-						println!("    {}", mixin);
-					}
+			}
+			match (side, environment) {
+				(Some(Environment::Server), Environment::Client) => {
+					problems.push("client-only, not loaded on a server".to_owned())
 				}
-				if !jar.1.mixins[Environment::Server].is_empty() {
-					println!("Server:");
-					for mixin in jar.1.mixins[Environment::Server].iter() {
-						println!("    {}", mixin);
-					}
+				(Some(Environment::Client), Environment::Server) => {
+					problems.push("server-only, not loaded on a client".to_owned())
 				}
+				_ => {}
 			}
-			if !matched_jars {
-				if mixin_cmd.filter.is_some() {
-					println!("No jars that match the given filter found!");
-				} else {
-					println!("No valid jars found!");
-				}
+			if !problems.is_empty() {
+				*flagged += 1;
+				line.push_str(&format!(" [{}]", problems.join(", ")));
+			}
+			lines.push(line);
+			for (name, contained_jar) in contained_jars {
+				recurse(contained_jar, name, Some(*environment), side, padding + 1, lines, flagged);
 			}
 		}
-		SubCommand::JarInJar(jar_in_jar) => {
-			if jar_in_jar.reverse {
-				struct FabricMod {
-					file_names: BTreeSet<String>,
-					parent_ids: BTreeSet<String>,
-				}
+	}
 
-				let mut reverse_tree: BTreeMap<String, FabricMod> = BTreeMap::new();
-
-				fn build_recurse(
-					jar: TraversedJar, file_name: &str, parent: Option<&str>, tree: &mut BTreeMap<String, FabricMod>,
-				) {
-					match jar {
-						TraversedJar::NonMod => {}
-						TraversedJar::FabricJar {
-							mod_id, contained_jars, ..
-						} => {
-							let entry = tree.entry(mod_id.clone()).or_insert(FabricMod {
-								file_names: BTreeSet::new(),
-								parent_ids: BTreeSet::new(),
-							});
-
-							entry.file_names.insert(file_name.to_string());
-							if let Some(parent) = parent {
-								entry.parent_ids.insert(parent.to_owned());
-							}
-							for jar in contained_jars {
-								build_recurse(jar.1, jar.0.as_str(), Some(mod_id.as_str()), tree);
-							}
-						}
-					}
-				}
+	let mut lines = vec![];
+	let mut flagged = 0;
+	for jar in jars {
+		recurse(&jar.1, file_name(&jar.0), None, side, 0, &mut lines, &mut flagged);
+	}
+	(lines, flagged)
+}
 
-				fn print_recurse(id: &str, tree: &BTreeMap<String, FabricMod>, padding: usize) {
-					let mod_data = &tree[id];
+/// Prints the authors, contributors and contact information of a mod, indented by one level
+fn print_contacts(authors: &[String], contributors: &[String], contact: &BTreeMap<String, String>) {
+	if !authors.is_empty() {
+		println!("    Authors: {}", authors.join(", "));
+	}
+	if !contributors.is_empty() {
+		println!("    Contributors: {}", contributors.join(", "));
+	}
+	for (kind, value) in contact {
+		let mut chars = kind.chars();
+		let kind: String = chars
+			.next()
+			.map(|c| c.to_uppercase().chain(chars).collect())
+			.unwrap_or_default();
+		println!("    {}: {}", kind, value);
+	}
+}
 
-					// Don't print on first level if it has no parents
-					if padding == 0 && mod_data.parent_ids.is_empty() {
-						return;
-					}
+/// Ids of mods supplied by Minecraft, Java and Fabric Loader themselves, which are never found as jars
+const BUILTIN_MODS: &[&str] = &["minecraft", "java", "fabricloader", "fabric-loader"];
 
-					println!(
-						"{}{} ({})",
-						"    ".repeat(padding),
-						id,
-						(&mod_data.file_names).iter().cloned().collect::<Vec<_>>().join(", ")
-					);
-					for parent_id in &mod_data.parent_ids {
-						print_recurse(&parent_id, tree, padding + 1);
-					}
-				}
+/// Ids of the mods that provide the common language adapters, for adapters that no mod in the folder provides
+const LANGUAGE_PROVIDERS: &[(&str, &str)] = &[
+	("kotlin", "fabric-language-kotlin"),
+	("scala", "fabric-language-scala"),
+	("groovy", "fabric-language-groovy"),
+];
 
-				for jar in processed_jars {
-					build_recurse(
-						jar.1,
-						jar.0
-							.file_name()
-							.map(|f| f.to_str().unwrap())
-							.unwrap_or(jar.0.to_str().unwrap()),
-						None,
-						&mut reverse_tree,
-					);
+/// Lists dependencies of mods (including nested mods) that are missing or aren't satisfied by any copy of the mod, taking
+/// ids in `provides` into account, and language adapters that mods use but no mod provides
+fn dependency_problems(jars: &[(PathBuf, TraversedJar)]) -> BTreeSet<String> {
+	struct ModCopy<'a> {
+		version: &'a str,
+		file_name: &'a str,
+		/// The mod that provides this id, if it isn't the mod's own id
+		provider: Option<&'a str>,
+	}
+
+	// Every copy of each mod id, including ids provided by other mods
+	let mut available: BTreeMap<&str, Vec<ModCopy>> = BTreeMap::new();
+	let mut dependents: Vec<(&str, &BTreeMap<String, Vec<String>>)> = vec![];
+	let mut used_adapters: Vec<(&str, &str)> = vec![];
+	let mut provided_adapters: BTreeSet<&str> = BTreeSet::new();
+	for jar in jars {
+		walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, _| {
+			if let TraversedJar::FabricJar {
+				mod_id,
+				mod_version,
+				depends,
+				provides,
+				adapters,
+				language_adapters,
+				..
+			} = nested
+			{
+				used_adapters.extend(adapters.iter().map(|adapter| (mod_id.as_str(), adapter.as_str())));
+				provided_adapters.extend(language_adapters.keys().map(String::as_str));
+				available.entry(mod_id).or_default().push(ModCopy {
+					version: mod_version,
+					file_name,
+					provider: None,
+				});
+				for provided in provides {
+					available.entry(provided).or_default().push(ModCopy {
+						version: mod_version,
+						file_name,
+						provider: Some(mod_id),
+					});
 				}
+				dependents.push((mod_id, depends));
+			}
+		});
+	}
 
-				for jar in &reverse_tree {
-					if let Some(ref filter) = jar_in_jar.filter {
-						if !jar.0.to_lowercase().contains(filter.to_lowercase().as_str()) {
-							continue;
-						}
-					}
-					print_recurse(&jar.0, &reverse_tree, 0);
+	let mut problems = BTreeSet::new();
+	for (mod_id, depends) in dependents {
+		for (dependency, ranges) in depends {
+			if BUILTIN_MODS.contains(&dependency.as_str()) {
+				continue;
+			}
+			let required = match version::VersionRange::parse_any(ranges) {
+				Some(required) => required,
+				None => {
+					problems.insert(format!(
+						"{} has an invalid version range for {}: {}",
+						mod_id,
+						dependency,
+						ranges.join(" || ")
+					));
+					continue;
 				}
-			} else {
-				fn print_recurse(jar: TraversedJar, name: &str, padding: usize) {
-					match jar {
-						TraversedJar::NonMod => {
-							println!("{}{} (Not a mod)", "    ".repeat(padding), name);
-						}
-						TraversedJar::FabricJar {
-							mod_id, contained_jars, ..
-						} => {
-							println!("{}{} ({})", "    ".repeat(padding), mod_id, name);
-							for jar in contained_jars {
-								print_recurse(jar.1, jar.0.as_str(), padding + 1);
-							}
-						}
-					}
+			};
+			let copies = match available.get(dependency.as_str()) {
+				Some(copies) => copies,
+				None => {
+					problems.insert(format!(
+						"{} requires {} {}, which is missing",
+						mod_id,
+						dependency,
+						ranges.join(" || ")
+					));
+					continue;
 				}
+			};
+			let satisfied = ranges.iter().any(|range| range == "*")
+				|| copies
+					.iter()
+					.any(|copy| version::Version::parse(copy.version).is_some_and(|v| required.contains(&v)));
+			if !satisfied {
+				let found: BTreeSet<String> = copies
+					.iter()
+					.map(|copy| match copy.provider {
+						Some(provider) => format!("{} ({}, provided by {})", copy.version, copy.file_name, provider),
+						None => format!("{} ({})", copy.version, copy.file_name),
+					})
+					.collect();
+				problems.insert(format!(
+					"{} requires {} {}, but found {}",
+					mod_id,
+					dependency,
+					ranges.join(" || "),
+					found.into_iter().collect::<Vec<_>>().join(", ")
+				));
+			}
+		}
+	}
+	for (mod_id, adapter) in used_adapters {
+		if provided_adapters.contains(adapter) {
+			continue;
+		}
+		match LANGUAGE_PROVIDERS.iter().find(|(name, _)| *name == adapter) {
+			Some((_, provider_id)) => problems.insert(format!(
+				"{} uses the {} language adapter, which is missing (provided by {})",
+				mod_id, adapter, provider_id
+			)),
+			None => problems.insert(format!(
+				"{} uses the {} language adapter, which no mod provides",
+				mod_id, adapter
+			)),
+		};
+	}
+	problems
+}
 
-				for jar in processed_jars {
-					if let Some(ref filter) = jar_in_jar.filter {
-						if let TraversedJar::FabricJar { mod_id, .. } = &jar.1 {
-							if !mod_id.to_lowercase().contains(filter.to_lowercase().as_str()) {
-								continue;
-							}
-						}
-					}
-					print_recurse(
-						jar.1,
-						jar.0
-							.file_name()
-							.map(|f| f.to_str().unwrap())
-							.unwrap_or(jar.0.to_str().unwrap()),
-						0,
-					);
-				}
+/// Maps every mod id supplied by a top-level jar, including ids in `provides`, to the jars supplying it; provided ids
+/// are described with the mod providing them in parentheses
+fn top_level_providers(jars: &[(PathBuf, TraversedJar)]) -> BTreeMap<&str, BTreeSet<String>> {
+	let mut providers: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+	for jar in jars {
+		if let TraversedJar::FabricJar { mod_id, provides, .. } = &jar.1 {
+			providers.entry(mod_id).or_default().insert(file_name(&jar.0).to_owned());
+			for provided in provides {
+				providers
+					.entry(provided)
+					.or_default()
+					.insert(format!("{} (provided by {})", file_name(&jar.0), mod_id));
 			}
 		}
-		SubCommand::AccessWidener(aw_cmd) => {
-			struct FabricJar {
-				file_names: BTreeSet<String>,
-				access_wideners: BTreeSet<String>
+	}
+	providers
+}
+
+fn is_csv(format: &Option<String>) -> bool {
+	format.as_deref() == Some("csv")
+}
+
+/// Formats a row of CSV, quoting fields that contain separators, quotes or line breaks
+fn csv_row<S: AsRef<str>>(fields: &[S]) -> String {
+	fields
+		.iter()
+		.map(|field| {
+			let field = field.as_ref();
+			if field.contains(&[',', '"', '\n', '\r'][..]) {
+				format!("\"{}\"", field.replace('"', "\"\""))
+			} else {
+				field.to_owned()
 			}
+		})
+		.collect::<Vec<_>>()
+		.join(",")
+}
 
-			let mut collated_jars: BTreeMap<String, FabricJar> = BTreeMap::new();
+/// Joins multiple values into a single CSV field
+fn csv_list<'a>(values: impl IntoIterator<Item = &'a String>) -> String {
+	values.into_iter().cloned().collect::<Vec<_>>().join("; ")
+}
 
-			fn recursively_collate(
-				dest: &mut BTreeMap<String, FabricJar>, jar: TraversedJar, file_name: &str, filter: Option<String>,
-			) {
-				if let TraversedJar::FabricJar {
-					mod_id,
-					contained_jars,
-					access_widener_contents,
-					..
-				} = jar
-				{
-					let collate_dest = dest.entry(mod_id).or_insert(FabricJar {
-						file_names: BTreeSet::new(),
-						access_wideners: BTreeSet::new(),
-					});
+/// Options for the order of listings, which are sorted by mod id by default
+#[derive(Clap, Debug)]
+struct SortOptions {
+	/// Sort the listing by this field; mixin-count and size use the largest copy of each mod
+	#[clap(long, possible_values = &["id", "name", "version", "file", "mixin-count", "size"], default_value = "id")]
+	sort: String,
+	/// Sort in descending order
+	#[clap(long)]
+	desc: bool,
+}
 
-					collate_dest.file_names.insert(file_name.to_owned());
-					if let Some(ref access_widener_contents) = access_widener_contents {
-						if let Some(ref filter) = filter {
-							if access_widener_contents.to_lowercase().contains(filter) {
-								collate_dest.access_wideners.insert(access_widener_contents.clone());
-							}
-						} else {
-							collate_dest.access_wideners.insert(access_widener_contents.clone());
-						}
-					}
+/// The fields of a mod that listings can be sorted by, merged across every copy of the mod
+#[derive(Default)]
+struct SortFields {
+	name: String,
+	/// The highest version
+	version: String,
+	/// The first file name, in alphabetical order
+	file: String,
+	mixin_count: usize,
+	size: u64,
+}
 
-					for contained_jar in contained_jars {
-						recursively_collate(dest, contained_jar.1, contained_jar.0.as_str(), (&filter).to_owned());
+fn sort_fields(jars: &[(PathBuf, TraversedJar)]) -> BTreeMap<String, SortFields> {
+	let mut fields: BTreeMap<String, SortFields> = BTreeMap::new();
+	for jar in jars {
+		walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, _| {
+			if let TraversedJar::FabricJar {
+				mod_name,
+				mod_id,
+				mod_version,
+				size,
+				mixins,
+				..
+			} = nested
+			{
+				let entry = fields.entry(mod_id.clone()).or_default();
+				if entry.name.is_empty() {
+					entry.name = mod_name.clone().unwrap_or_else(|| mod_id.clone()).to_lowercase();
+				}
+				if entry.version.is_empty() || version::compare(mod_version, &entry.version) == Ordering::Greater {
+					entry.version = mod_version.clone();
+				}
+				if entry.file.is_empty() || file_name < entry.file.as_str() {
+					entry.file = file_name.to_owned();
+				}
+				entry.mixin_count = entry.mixin_count.max(mixins.values().map(Vec::len).sum());
+				entry.size = entry.size.max(*size);
+			} else if let TraversedJar::OtherLoaderJar { mods, size, .. } = nested {
+				for forge_mod in mods {
+					let entry = fields.entry(forge_mod.mod_id.clone()).or_default();
+					if entry.name.is_empty() {
+						entry.name = forge_mod.mod_name.as_ref().unwrap_or(&forge_mod.mod_id).to_lowercase();
 					}
+					if entry.version.is_empty() {
+						entry.version = forge_mod.mod_version.clone().unwrap_or_default();
+					}
+					if entry.file.is_empty() || file_name < entry.file.as_str() {
+						entry.file = file_name.to_owned();
+					}
+					entry.size = entry.size.max(*size);
 				}
 			}
+		});
+	}
+	fields
+}
 
-			let filter = (&aw_cmd.filter).as_ref();
-			for jar in processed_jars {
-				recursively_collate(
-					&mut collated_jars,
-					jar.1,
-					jar.0
-						.file_name()
-						.map(|f| f.to_str().unwrap())
-						.unwrap_or(jar.0.to_str().unwrap()),
-					filter.map(|filter| filter.as_str().to_lowercase()),
-				);
-			}
-
-			let mut matched_jars = false;
-			for jar in &collated_jars {
-				if jar.1.access_wideners.is_empty() {
-					continue;
-				}
+impl SortOptions {
+	fn needs_mixins(&self) -> bool {
+		self.sort == "mixin-count"
+	}
 
-				matched_jars = true;
-				println!(
-					"{} ({})",
-					jar.0,
-					(&jar.1.file_names).iter().cloned().collect::<Vec<String>>().join(", ")
-				);
-				for aw in jar.1.access_wideners.iter() {
-					for line in aw.lines() {
-						println!("    {}", line);
-					}
-				}
+	/// Orders a listing by mod id, using `fields` (from [`sort_fields`]) for the other sort orders
+	fn sorted<K: AsRef<str>, V>(&self, listing: BTreeMap<K, V>, fields: &BTreeMap<String, SortFields>) -> Vec<(K, V)> {
+		let default_fields = SortFields::default();
+		let mut entries: Vec<(usize, K, V)> = listing.into_iter().enumerate().map(|(i, (k, v))| (i, k, v)).collect();
+		entries.sort_by(|a, b| {
+			let a_fields = fields.get(a.1.as_ref()).unwrap_or(&default_fields);
+			let b_fields = fields.get(b.1.as_ref()).unwrap_or(&default_fields);
+			let order = match self.sort.as_str() {
+				"name" => a_fields.name.cmp(&b_fields.name),
+				"version" => version::compare(&a_fields.version, &b_fields.version),
+				"file" => a_fields.file.cmp(&b_fields.file),
+				"mixin-count" => a_fields.mixin_count.cmp(&b_fields.mixin_count),
+				"size" => a_fields.size.cmp(&b_fields.size),
+				_ => Ordering::Equal,
 			}
-			if !matched_jars {
-				if aw_cmd.filter.is_some() {
-					println!("No jars that match the given filter found!");
-				} else {
-					println!("No jars with AWs found!");
-				}
+			.then(a.0.cmp(&b.0));
+			if self.desc {
+				order.reverse()
+			} else {
+				order
 			}
+		});
+		entries.into_iter().map(|(_, k, v)| (k, v)).collect()
+	}
+}
+
+/// Formats a number of bytes with a binary unit, e.g. 1.5 MiB
+fn format_size(bytes: u64) -> String {
+	const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+	if bytes < 1024 {
+		return format!("{} B", bytes);
+	}
+	let mut size = bytes as f64 / 1024.0;
+	let mut unit = 0;
+	while size >= 1024.0 && unit < UNITS.len() - 1 {
+		size /= 1024.0;
+		unit += 1;
+	}
+	format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Suffixes that launchers add to the names of jars to disable them
+const DISABLED_SUFFIXES: &[&str] = &[".jar.disabled", ".jar.old"];
+
+fn is_disabled(path: &Path) -> bool {
+	DISABLED_SUFFIXES.iter().any(|suffix| file_name(path).ends_with(suffix))
+}
+
+/// The file name of a top-level jar, marked if the jar is disabled
+fn display_name(path: &Path) -> String {
+	if is_disabled(path) {
+		format!("{} (disabled)", file_name(path))
+	} else {
+		file_name(path).to_owned()
+	}
+}
+
+fn file_name(path: &Path) -> &str {
+	path.file_name()
+		.map(|f| f.to_str().unwrap())
+		.unwrap_or(path.to_str().unwrap())
+}
+
+#[derive(Clap, Debug)]
+#[clap(version = crate_version!(), setting(AppSettings::UnifiedHelpMessage), setting(AppSettings::GlobalVersion))]
+struct Opts {
+	#[clap(flatten)]
+	scan: ScanOptions,
+	/// Don't print progress messages such as "Reading mods in the current folder..." or the progress bar to stderr (the
+	/// bar is only shown when stderr is a terminal); for semver-check, only set the exit status without printing anything
+	#[clap(short, long, global = true)]
+	quiet: bool,
+	/// When to color the output; auto (the default) colors it if stdout is a terminal and NO_COLOR isn't set
+	#[clap(long, global = true, possible_values = &["auto", "always", "never"])]
+	color: Option<String>,
+	/// Folder to read mods from, instead of the current folder
+	#[clap(long, global = true)]
+	dir: Option<PathBuf>,
+	/// Read mods from a snapshot saved with raw --output instead of reading the jars in a folder. Commands that read
+	/// the jars themselves rather than their metadata, such as hash, strings or extract, can't be used with snapshots.
+	#[clap(long, global = true, conflicts_with = "dir")]
+	from_snapshot: Option<PathBuf>,
+	/// Keep running, and print what changed in the output whenever jars in the folder are added, removed or replaced
+	#[clap(long, global = true)]
+	watch: bool,
+	#[clap(subcommand)]
+	subcmd: SubCommand,
+}
+
+#[derive(Clap, Debug)]
+enum SubCommand {
+	Mixin(mixin::MixinCommand),
+	#[clap(alias = "jij")]
+	JarInJar(jar_in_jar::JarInJarCommand),
+	#[clap(alias = "aw")]
+	AccessWidener(access_widener::AccessWidenerCommand),
+	Raw(snapshot::RawCommand),
+	List(list::ListCommand),
+	Info(list::InfoCommand),
+	Verify(verify::VerifyCommand),
+	BundleReport(bundle::BundleReportCommand),
+	Hash(hash::HashCommand),
+	Lookup(lookup::LookupCommand),
+	Outdated(lookup::OutdatedCommand),
+	Licenses(licenses::LicensesCommand),
+	Contacts(licenses::ContactsCommand),
+	Icons(icons::IconsCommand),
+	Env(environment::EnvCommand),
+	McVersions(requirements::McVersionsCommand),
+	LoaderReq(requirements::LoaderReqCommand),
+	Loaders(loaders::LoadersCommand),
+	Transformers(transformers::TransformersCommand),
+	Scan(scan::ScanCommand),
+	Strings(strings::StringsCommand),
+	Classes(classes::ClassesCommand),
+	FabricApi(fabric_api::FabricApiCommand),
+	Adapters(loaders::AdaptersCommand),
+	Content(content::ContentCommand),
+	Lang(lang::LangCommand),
+	VerifyPack(pack::VerifyPackCommand),
+	#[clap(alias = "modmenu")]
+	ModMenu(mod_menu::ModMenuCommand),
+	Query(query::QueryCommand),
+	Touches(touches::TouchesCommand),
+	JavaVersion(requirements::JavaVersionCommand),
+	SemverCheck(SemverCheckCommand),
+	Check(deps::CheckCommand),
+	Lint(lint::LintCommand),
+	Duplicates(duplicates::DuplicatesCommand),
+	Deps(deps::DepsCommand),
+	Report(report::ReportCommand),
+	Diff(DiffCommand),
+	DiffJar(DiffJarCommand),
+	Stats(stats::StatsCommand),
+	Size(stats::SizeCommand),
+	Extract(extract::ExtractCommand),
+	Flatten(flatten::FlattenCommand),
+	Tui(tui::TuiCommand),
+	Serve(ServeCommand),
+}
+
+impl SubCommand {
+	/// The command's implementation, or `None` for the commands that don't read the mods in the current folder
+	fn command(&self) -> Option<&dyn JarCommand> {
+		Some(match self {
+			SubCommand::Mixin(command) => command,
+			SubCommand::JarInJar(command) => command,
+			SubCommand::AccessWidener(command) => command,
+			SubCommand::Raw(command) => command,
+			SubCommand::List(command) => command,
+			SubCommand::Info(command) => command,
+			SubCommand::Verify(command) => command,
+			SubCommand::BundleReport(command) => command,
+			SubCommand::Hash(command) => command,
+			SubCommand::Lookup(command) => command,
+			SubCommand::Outdated(command) => command,
+			SubCommand::Licenses(command) => command,
+			SubCommand::Contacts(command) => command,
+			SubCommand::Icons(command) => command,
+			SubCommand::Env(command) => command,
+			SubCommand::McVersions(command) => command,
+			SubCommand::LoaderReq(command) => command,
+			SubCommand::Loaders(command) => command,
+			SubCommand::Transformers(command) => command,
+			SubCommand::Scan(command) => command,
+			SubCommand::Strings(command) => command,
+			SubCommand::Classes(command) => command,
+			SubCommand::FabricApi(command) => command,
+			SubCommand::Adapters(command) => command,
+			SubCommand::Content(command) => command,
+			SubCommand::Lang(command) => command,
+			SubCommand::VerifyPack(command) => command,
+			SubCommand::ModMenu(command) => command,
+			SubCommand::Query(command) => command,
+			SubCommand::Touches(command) => command,
+			SubCommand::JavaVersion(command) => command,
+			SubCommand::Check(command) => command,
+			SubCommand::Lint(command) => command,
+			SubCommand::Duplicates(command) => command,
+			SubCommand::Deps(command) => command,
+			SubCommand::Report(command) => command,
+			SubCommand::Stats(command) => command,
+			SubCommand::Size(command) => command,
+			SubCommand::Extract(command) => command,
+			SubCommand::Flatten(command) => command,
+			SubCommand::Tui(command) => command,
+			SubCommand::SemverCheck(_) | SubCommand::Diff(_) | SubCommand::DiffJar(_) | SubCommand::Serve(_) => return None,
+		})
+	}
+
+	fn detail_level(&self) -> DetailLevel {
+		self.command().map_or(DetailLevel::Full, JarCommand::detail_level)
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		match self {
+			SubCommand::Serve(_) => Some("serve"),
+			_ => self.command().and_then(JarCommand::rereads_jars),
+		}
+	}
+
+	fn is_scripted(&self) -> bool {
+		self.command().is_some_and(JarCommand::is_scripted)
+	}
+}
+
+/// A command that reads the mods in the current folder
+trait JarCommand {
+	/// How much of each jar the command needs
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Full
+	}
+
+	/// The name of the command if it reads or deletes the jars after they have been traversed, which can't be done with a
+	/// snapshot. There's no default, so that every command has to decide whether it can be used with one
+	fn rereads_jars(&self) -> Option<&'static str>;
+
+	/// Whether the command has been asked for bare output that shouldn't be mixed with progress messages
+	fn is_scripted(&self) -> bool {
+		false
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()>;
+}
+
+/// What a command is told about how the mods were read
+struct RunContext<'a> {
+	failed_jars: &'a [PathBuf],
+	scan: &'a ScanOptions,
+	quiet: bool,
+}
+
+/// Checks whether a version satisfies a Fabric version range, without reading any mods
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+struct SemverCheckCommand {
+	/// The version to check
+	version: String,
+	/// The version range, e.g. ">=1.16.2 <1.17" or "~1.16.2"; multiple ranges are satisfied if any of them is (like
+	/// an array in fabric.mod.json)
+	#[clap(required = true)]
+	range: Vec<String>,
+}
+
+/// Compares the mods in two folders, listing added, removed and changed mods
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+struct DiffCommand {
+	/// The folder with the old versions of the mods, or a snapshot saved with raw --output
+	old_dir: PathBuf,
+	/// The folder with the new versions of the mods, or a snapshot saved with raw --output
+	new_dir: PathBuf,
+}
+
+/// Compares two builds of a single jar, listing changes to its metadata, mixins, nested jars, access widener and classes
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+struct DiffJarCommand {
+	/// The old build of the jar
+	old_jar: PathBuf,
+	/// The new build of the jar
+	new_jar: PathBuf,
+}
+
+/// Serves the contents of the mods in the current folder as JSON over HTTP, for launchers and dashboards
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+struct ServeCommand {
+	/// Port to listen on
+	#[clap(long, default_value = "8080")]
+	port: u16,
+	/// Address to listen on; use 0.0.0.0 to allow connections from other machines
+	#[clap(long, default_value = "127.0.0.1")]
+	host: String,
+}
+
+/// Exit status when `check` finds dependency problems
+const EXIT_DEPENDENCY_PROBLEMS: i32 = 2;
+/// Exit status when `duplicates` finds mods supplied by multiple jars
+const EXIT_DUPLICATES: i32 = 3;
+/// Exit status when a filter doesn't match anything
+const EXIT_NO_MATCHES: i32 = 4;
+/// Exit status when the command succeeded, but some jars couldn't be read
+const EXIT_UNREADABLE_JARS: i32 = 5;
+/// Exit status when `lint` or `aw --lint` finds errors
+const EXIT_LINT_ERRORS: i32 = 6;
+/// Exit status when `scan` finds jars matching a malware signature
+const EXIT_MALWARE: i32 = 7;
+/// `verify-pack` found files that are missing, extra or different from the manifest
+const EXIT_PACK_MISMATCH: i32 = 8;
+
+fn main() -> Result<()> {
+	let mut opts: Opts = Opts::parse();
+	config::load()?.apply(&mut opts);
+	let dir = opts.dir.clone().unwrap_or_else(|| PathBuf::from("."));
+	let folder_description = match &opts.dir {
+		Some(dir) => dir.display().to_string(),
+		None => "the current folder".to_owned(),
+	};
+
+	color::init(opts.color.as_deref().unwrap_or("auto"));
+
+	if let Some(threads) = opts.scan.threads {
+		rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
+	}
+
+	if opts.from_snapshot.is_some() {
+		if opts.watch {
+			anyhow::bail!("--from-snapshot can't be used with --watch, which needs to read the jars again");
+		}
+		if let Some(command) = opts.subcmd.rereads_jars() {
+			anyhow::bail!(
+				"--from-snapshot can't be used with {}, which needs the jars themselves",
+				command
+			);
+		}
+	}
+
+	if opts.watch {
+		let args: Vec<OsString> = env::args_os().skip(1).filter(|arg| arg != "--watch").collect();
+		return watch::watch(&dir, &args, opts.quiet);
+	}
+
+	if let SubCommand::SemverCheck(semver_check_cmd) = &opts.subcmd {
+		let version = version::Version::parse(&semver_check_cmd.version)
+			.ok_or_else(|| anyhow::anyhow!("{} is not a valid semantic version", semver_check_cmd.version))?;
+		let range = version::VersionRange::parse_any(&semver_check_cmd.range)
+			.ok_or_else(|| anyhow::anyhow!("{} is not a valid version range", semver_check_cmd.range.join(" || ")))?;
+		let satisfied = range.contains(&version);
+		if !opts.quiet {
+			println!(
+				"{} {} {} (interpreted as {})",
+				version,
+				if satisfied { "satisfies" } else { "doesn't satisfy" },
+				semver_check_cmd.range.join(" || "),
+				range
+			);
+		}
+		if !satisfied {
+			process::exit(1);
+		}
+		return Ok(());
+	}
+
+	if let SubCommand::Diff(diff_cmd) = &opts.subcmd {
+		if !opts.quiet {
+			eprintln!(
+				"Comparing mods in {} and {}...",
+				diff_cmd.old_dir.display(),
+				diff_cmd.new_dir.display()
+			);
 		}
-		SubCommand::Raw(_raw) => {
-			for jar in processed_jars {
-				println!(
-					"{} {:#?}",
-					jar.0
-						.file_name()
-						.map(|f| f.to_str().unwrap())
-						.unwrap_or(jar.0.to_str().unwrap()),
-					jar.1
-				);
+		// Either folder can be a snapshot, to compare a folder with how it was before
+		let read_folder = |path: &Path| {
+			if path.is_file() {
+				snapshot::read(path)
+			} else {
+				scan_dir(path, DetailLevel::Full, &opts.scan, !opts.quiet)
 			}
+		};
+		if !diff::print_folder_diff(&read_folder(&diff_cmd.old_dir)?.jars, &read_folder(&diff_cmd.new_dir)?.jars) {
+			println!("No changes found");
+		}
+		return Ok(());
+	}
+
+	if let SubCommand::DiffJar(diff_jar_cmd) = &opts.subcmd {
+		if !diff::print_jar_diff(&diff_jar_cmd.old_jar, &diff_jar_cmd.new_jar, &opts.scan)? {
+			println!("No changes found");
 		}
+		return Ok(());
 	}
 
+	if let SubCommand::Serve(serve_cmd) = &opts.subcmd {
+		if !opts.quiet {
+			eprintln!("Reading mods in {}...", folder_description);
+		}
+		let address = format!("{}:{}", serve_cmd.host, serve_cmd.port);
+		return serve::serve(&address, &dir, &opts.scan, !opts.quiet);
+	}
+
+	// Report mistakes in the query before spending time reading the jars
+	if let SubCommand::Query(query_cmd) = &opts.subcmd {
+		query::Query::parse(&query_cmd.query)?;
+	}
+
+	let show_progress = !opts.quiet && !opts.subcmd.is_scripted();
+	let ScannedDir {
+		jars: processed_jars,
+		failed: failed_jars,
+	} = if let Some(snapshot) = &opts.from_snapshot {
+		if show_progress {
+			eprintln!("Reading mods from the snapshot {}...", snapshot.display());
+		}
+		let scanned = snapshot::read(snapshot)?;
+		// Snapshots don't record the hashes of jars that aren't mods, which duplicates compares
+		if matches!(opts.subcmd, SubCommand::Duplicates(_))
+			&& scanned.jars.iter().any(|jar| matches!(jar.1, TraversedJar::NonMod))
+		{
+			anyhow::bail!(
+				"{} contains jars that aren't mods, which duplicates needs to read again, so it can't be used with \
+				 --from-snapshot",
+				snapshot.display()
+			);
+		}
+		scanned
+	} else {
+		if show_progress {
+			eprintln!("Reading mods in {}...", folder_description);
+		}
+		scan_dir(&dir, opts.subcmd.detail_level(), &opts.scan, !opts.quiet)?
+	};
+
+	let command = opts.subcmd.command().expect("this command is handled before reading mods");
+	let context = RunContext {
+		failed_jars: &failed_jars,
+		scan: &opts.scan,
+		quiet: opts.quiet,
+	};
+	command.run(processed_jars, &context)?;
+	if !failed_jars.is_empty() {
+		process::exit(EXIT_UNREADABLE_JARS);
+	}
 	Ok(())
 }
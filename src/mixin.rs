@@ -0,0 +1,240 @@
+//! Listing the mixins that mods apply
+
+use crate::{
+	color, csv_list, csv_row, display_name, is_csv, sort_fields, version, Environment, JarCommand, MixinConfigInfo, RunContext,
+	SortOptions, TraversedJar, EXIT_NO_MATCHES,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use enum_map::{enum_map, EnumMap};
+use std::{
+	cmp::Ordering,
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+	process,
+};
+
+/// Lists mixins in mods in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct MixinCommand {
+	/// Filter the list of mixins using this search string
+	#[clap(long)]
+	filter: Option<String>,
+	/// Only list the mixins that are applied on this side, including those applied on both sides (both only lists
+	/// mixins applied on both sides). Mixins in mods that don't run on the side are left out.
+	#[clap(long, possible_values = &["client", "server", "both"])]
+	env: Option<String>,
+	/// Only list mods with mixin configs that require a newer version of Mixin than this one, e.g. the version your
+	/// loader ships
+	#[clap(long)]
+	min_version: Option<String>,
+	/// Print the output in this format instead of a human-readable listing
+	#[clap(long, possible_values = &["text", "csv"])]
+	pub format: Option<String>,
+	#[clap(flatten)]
+	sort: SortOptions,
+}
+
+impl JarCommand for MixinCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn is_scripted(&self) -> bool {
+		is_csv(&self.format)
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		struct FabricJar {
+			file_names: BTreeSet<String>,
+			mixins: EnumMap<Environment, BTreeSet<String>>,
+			configs: BTreeSet<MixinConfigInfo>,
+			/// Version and hash of every distinct copy of this mod that has been collated
+			seen_copies: BTreeSet<(String, String)>,
+			/// Ids of the mods that bundle this mod as a nested jar
+			bundled_by: BTreeSet<String>,
+		}
+
+		let mut collated_jars: BTreeMap<String, FabricJar> = BTreeMap::new();
+
+		fn matches(dest: &str) -> impl FnMut(&&String) -> bool + '_ {
+			move |name: &&String| name.to_lowercase().contains(dest)
+		}
+
+		/// Whether mixins declared for `mixin_env` in a mod declared for `mod_env` are applied on `side`
+		fn applies_on(side: Environment, mod_env: Environment, mixin_env: Environment) -> bool {
+			let runs_on_side = |env: Environment| env == Environment::Both || env == side;
+			runs_on_side(mod_env) && runs_on_side(mixin_env)
+		}
+
+		fn recursively_collate(
+			dest: &mut BTreeMap<String, FabricJar>, jar: TraversedJar, file_name: &str, parent: Option<&str>,
+			filter: Option<String>, side: Option<Environment>, min_version: Option<&str>,
+		) {
+			if let TraversedJar::FabricJar {
+				mod_id,
+				mod_version,
+				hash,
+				environment,
+				contained_jars,
+				mut mixins,
+				mixin_configs,
+				..
+			} = jar
+			{
+				if let Some(side) = side {
+					for (mixin_env, env_mixins) in mixins.iter_mut() {
+						if !applies_on(side, environment, mixin_env) {
+							env_mixins.clear();
+						}
+					}
+				}
+
+				let collate_dest = dest.entry(mod_id.clone()).or_insert(FabricJar {
+					file_names: BTreeSet::new(),
+					mixins: enum_map! { _ => BTreeSet::new() },
+					configs: BTreeSet::new(),
+					seen_copies: BTreeSet::new(),
+					bundled_by: BTreeSet::new(),
+				});
+
+				collate_dest.file_names.insert(file_name.to_owned());
+				if let Some(parent) = parent {
+					collate_dest.bundled_by.insert(parent.to_owned());
+				}
+				// Identical copies have identical mixins and nested jars, so only collate them once
+				if !collate_dest.seen_copies.insert((mod_version, hash)) {
+					return;
+				}
+
+				if let Some(ref filter) = filter {
+					collate_dest.mixins[Environment::Both]
+						.extend(mixins[Environment::Both].iter().filter(matches(filter)).cloned());
+					collate_dest.mixins[Environment::Client]
+						.extend(mixins[Environment::Client].iter().filter(matches(filter)).cloned());
+					collate_dest.mixins[Environment::Server]
+						.extend(mixins[Environment::Server].iter().filter(matches(filter)).cloned());
+				} else {
+					collate_dest.mixins[Environment::Both].extend(mixins[Environment::Both].iter().cloned());
+					collate_dest.mixins[Environment::Client].extend(mixins[Environment::Client].iter().cloned());
+					collate_dest.mixins[Environment::Server].extend(mixins[Environment::Server].iter().cloned());
+				}
+				collate_dest.configs.extend(mixin_configs.into_iter().filter(|config| {
+					match min_version {
+						Some(min_version) => config
+							.min_version
+							.as_deref()
+							.is_some_and(|required| version::compare(required, min_version) == Ordering::Greater),
+						None => true,
+					}
+				}));
+
+				for contained_jar in contained_jars {
+					recursively_collate(
+						dest,
+						contained_jar.1,
+						contained_jar.0.as_str(),
+						Some(mod_id.as_str()),
+						filter.to_owned(),
+						side,
+						min_version,
+					);
+				}
+			}
+		}
+
+		let filter = self.filter.as_ref();
+		let side = self.env.as_deref().map(|env| match env {
+			"client" => Environment::Client,
+			"server" => Environment::Server,
+			_ => Environment::Both,
+		});
+		let fields = sort_fields(&processed_jars);
+		for jar in processed_jars {
+			recursively_collate(
+				&mut collated_jars,
+				jar.1,
+				&display_name(&jar.0),
+				None,
+				filter.map(|filter| filter.as_str().to_lowercase()),
+				side,
+				self.min_version.as_deref(),
+			);
+		}
+		let mut collated_jars = self.sort.sorted(collated_jars, &fields);
+
+		if is_csv(&self.format) {
+			println!("{}", csv_row(&["mod_id", "files", "environment", "mixin"]));
+			for (mod_id, jar) in &collated_jars {
+				let files = csv_list(&jar.file_names);
+				for (environment, mixins) in &jar.mixins {
+					for mixin in mixins {
+						println!("{}", csv_row(&[mod_id, &files, &environment.to_string(), mixin]));
+					}
+				}
+			}
+			return Ok(());
+		}
+
+		// If there is a filter, hide jars that don't match the filter
+		let filtered = self.filter.is_some() || side.is_some();
+		collated_jars.retain(|(_, jar)| {
+			(!filtered || jar.mixins.values().any(|v| !v.is_empty())) && (self.min_version.is_none() || !jar.configs.is_empty())
+		});
+		let filtered = filtered || self.min_version.is_some();
+		let id_width = collated_jars
+			.iter()
+			.map(|(id, _)| id.chars().count())
+			.max()
+			.unwrap_or_default();
+
+		let mut matched_jars = false;
+		for jar in &collated_jars {
+			matched_jars = true;
+			print!(
+				"{} ({})",
+				color::padded(&jar.0, id_width, color::bold),
+				jar.1.file_names.iter().cloned().collect::<Vec<String>>().join(", ")
+			);
+			if jar.1.bundled_by.len() > 1 {
+				print!(" [bundled by {} mods]", jar.1.bundled_by.len());
+			}
+			println!();
+			for mixin in jar.1.mixins[Environment::Both].iter() {
+				println!("    {}", mixin);
+			}
+			if !jar.1.mixins[Environment::Client].is_empty() {
+				println!("{}", color::cyan("Client:"));
+				for mixin in jar.1.mixins[Environment::Client].iter() {
+					println!("    {}", mixin);
+				}
+			}
+			if !jar.1.mixins[Environment::Server].is_empty() {
+				println!("{}", color::yellow("Server:"));
+				for mixin in jar.1.mixins[Environment::Server].iter() {
+					println!("    {}", mixin);
+				}
+			}
+			if !jar.1.configs.is_empty() {
+				println!("Configs:");
+				for config in &jar.1.configs {
+					println!("    {}", config.describe());
+					for class in &config.missing_classes {
+						println!("        {}", color::red(&format!("Missing class: {}", class)));
+					}
+				}
+			}
+		}
+		if !matched_jars {
+			if filtered {
+				println!("No jars that match the given filter found!");
+				process::exit(EXIT_NO_MATCHES);
+			} else {
+				println!("No valid jars found!");
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,139 @@
+//! Listing how mods present themselves in Mod Menu
+
+use crate::{color, file_name, walk_jars, DetailLevel, JarCommand, ModMenuInfo, RunContext, TraversedJar};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Lists the mods in the current folder as Mod Menu shows them, with their badges, links and child mods grouped under
+/// their parents
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct ModMenuCommand {
+	/// Leave out mods marked as libraries, like Mod Menu does by default
+	#[clap(long)]
+	hide_libraries: bool,
+}
+
+impl JarCommand for ModMenuCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		struct MenuEntry<'a> {
+			name: &'a str,
+			version: &'a str,
+			file_name: &'a str,
+			mod_menu: &'a ModMenuInfo,
+		}
+
+		let mut entries: BTreeMap<&str, MenuEntry> = BTreeMap::new();
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, _| {
+				if let TraversedJar::FabricJar {
+					mod_id,
+					mod_name,
+					mod_version,
+					mod_menu,
+					..
+				} = nested
+				{
+					entries.entry(mod_id).or_insert(MenuEntry {
+						name: mod_name.as_deref().unwrap_or(mod_id),
+						version: mod_version,
+						file_name,
+						mod_menu,
+					});
+				}
+			});
+		}
+
+		// Placeholder parents that aren't installed, with their names if they have one
+		let mut placeholders: BTreeMap<&str, Option<&str>> = BTreeMap::new();
+		let mut children: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+		let mut roots: Vec<&str> = vec![];
+		for (mod_id, entry) in &entries {
+			if self.hide_libraries && entry.mod_menu.is_library() {
+				continue;
+			}
+			match &entry.mod_menu.parent {
+				Some(parent) if parent != mod_id => {
+					children.entry(parent).or_default().push(mod_id);
+					if !entries.contains_key(parent.as_str()) {
+						let name = placeholders.entry(parent).or_default();
+						*name = name.or(entry.mod_menu.parent_name.as_deref());
+					}
+				}
+				_ => roots.push(mod_id),
+			}
+		}
+		roots.extend(placeholders.keys());
+
+		// Mod Menu sorts mods by name
+		let sort_name = |mod_id: &&str| match entries.get(mod_id) {
+			Some(entry) => entry.name.to_lowercase(),
+			None => placeholders[mod_id].unwrap_or(mod_id).to_lowercase(),
+		};
+		roots.sort_by_key(sort_name);
+		for mod_ids in children.values_mut() {
+			mod_ids.sort_by_key(sort_name);
+		}
+
+		fn print_entry(
+			mod_id: &str, entries: &BTreeMap<&str, MenuEntry>, placeholders: &BTreeMap<&str, Option<&str>>,
+			children: &BTreeMap<&str, Vec<&str>>, padding: usize,
+		) {
+			let indent = "    ".repeat(padding);
+			match entries.get(mod_id) {
+				Some(entry) => {
+					let badges = if entry.mod_menu.badges.is_empty() {
+						String::new()
+					} else {
+						format!(" [{}]", entry.mod_menu.badges.join(", "))
+					};
+					println!(
+						"{}{} {} ({}, {}){}",
+						indent,
+						color::bold(entry.name),
+						entry.version,
+						mod_id,
+						entry.file_name,
+						badges
+					);
+					for (name, link) in &entry.mod_menu.links {
+						println!("{}    {}: {}", indent, name.trim_start_matches("modmenu."), link);
+					}
+					if entry.mod_menu.update_checker == Some(false) {
+						println!("{}    Update checker disabled", indent);
+					}
+				}
+				None => println!(
+					"{}{} ({}, not installed)",
+					indent,
+					color::bold(placeholders[mod_id].unwrap_or(mod_id)),
+					mod_id
+				),
+			}
+			for child in children.get(mod_id).into_iter().flatten() {
+				print_entry(child, entries, placeholders, children, padding + 1);
+			}
+		}
+
+		for mod_id in &roots {
+			print_entry(mod_id, &entries, &placeholders, &children, 0);
+		}
+		if entries.is_empty() {
+			println!("No valid jars found!");
+		} else {
+			let libraries = entries.values().filter(|entry| entry.mod_menu.is_library()).count();
+			println!("{} mods, {} of them libraries", entries.len(), libraries);
+		}
+
+		Ok(())
+	}
+}
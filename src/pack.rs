@@ -0,0 +1,289 @@
+//! Reading the files a modpack expects from a Modrinth (`modrinth.index.json` or `.mrpack`) or packwiz (`pack.toml`)
+//! manifest
+
+use crate::{color, file_name, hash, DetailLevel, JarCommand, RunContext, TraversedJar, EXIT_PACK_MISMATCH};
+use anyhow::{Context, Result};
+use clap::{AppSettings, Clap};
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::{
+	collections::BTreeMap,
+	fs,
+	io::Read,
+	path::{Path, PathBuf},
+	process,
+};
+use zip::ZipArchive;
+
+/// A file that a modpack manifest expects, with its hash
+pub struct ExpectedFile {
+	/// Path relative to the root of the pack, such as `mods/sodium.jar`
+	pub path: String,
+	/// The hash algorithm, which is one of [hash::ALGORITHMS] if the hash can be checked
+	pub algorithm: String,
+	pub hash: String,
+	pub client: bool,
+	pub server: bool,
+}
+
+impl ExpectedFile {
+	pub fn can_check_hash(&self) -> bool {
+		hash::ALGORITHMS.contains(&self.algorithm.as_str())
+	}
+}
+
+#[derive(Deserialize)]
+struct ModrinthIndex {
+	files: Vec<ModrinthFile>,
+}
+
+#[derive(Deserialize)]
+struct ModrinthFile {
+	path: String,
+	hashes: BTreeMap<String, String>,
+	env: Option<ModrinthEnv>,
+}
+
+/// Whether a file is `required`, `optional` or `unsupported` on each side
+#[derive(Deserialize)]
+struct ModrinthEnv {
+	client: String,
+	server: String,
+}
+
+#[derive(Deserialize)]
+struct PackwizPack {
+	index: PackwizIndexReference,
+}
+
+#[derive(Deserialize)]
+struct PackwizIndexReference {
+	file: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PackwizIndex {
+	hash_format: String,
+	#[serde(default)]
+	files: Vec<PackwizIndexFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PackwizIndexFile {
+	file: String,
+	hash: String,
+	hash_format: Option<String>,
+	/// Whether the file is a `.pw.toml` file describing a file to download, rather than a file of the pack itself
+	#[serde(default)]
+	metafile: bool,
+}
+
+#[derive(Deserialize)]
+struct PackwizMetafile {
+	filename: String,
+	/// `client`, `server` or `both`
+	side: Option<String>,
+	download: PackwizDownload,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PackwizDownload {
+	hash_format: String,
+	hash: String,
+}
+
+fn read_modrinth_index(index: ModrinthIndex) -> Vec<ExpectedFile> {
+	index
+		.files
+		.into_iter()
+		.map(|file| {
+			// Modrinth always includes SHA-1 and SHA-512 hashes
+			let (algorithm, hash) = file
+				.hashes
+				.get_key_value("sha512")
+				.or_else(|| file.hashes.iter().next())
+				.map(|(algorithm, hash)| (algorithm.clone(), hash.clone()))
+				.unwrap_or_default();
+			let (client, server) = match &file.env {
+				Some(env) => (env.client != "unsupported", env.server != "unsupported"),
+				None => (true, true),
+			};
+			ExpectedFile {
+				path: file.path,
+				algorithm,
+				hash,
+				client,
+				server,
+			}
+		})
+		.collect()
+}
+
+fn read_toml<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+	let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+	toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn read_packwiz_pack(pack_file: &Path) -> Result<Vec<ExpectedFile>> {
+	let pack: PackwizPack = read_toml(pack_file)?;
+	let index_file = pack_file.parent().unwrap_or(Path::new("")).join(&pack.index.file);
+	let index: PackwizIndex = read_toml(&index_file)?;
+	// Paths in the index are relative to the folder of the index
+	let root = index_file.parent().unwrap_or(Path::new(""));
+
+	let mut files = vec![];
+	for file in &index.files {
+		if !file.metafile {
+			files.push(ExpectedFile {
+				path: file.file.clone(),
+				algorithm: file.hash_format.clone().unwrap_or_else(|| index.hash_format.clone()),
+				hash: file.hash.clone(),
+				client: true,
+				server: true,
+			});
+			continue;
+		}
+		let metafile: PackwizMetafile = read_toml(&root.join(&file.file))?;
+		let folder = file.file.rsplit_once('/').map_or("", |(folder, _)| folder);
+		let side = metafile.side.as_deref().unwrap_or("both");
+		files.push(ExpectedFile {
+			path: if folder.is_empty() {
+				metafile.filename
+			} else {
+				format!("{}/{}", folder, metafile.filename)
+			},
+			algorithm: metafile.download.hash_format,
+			hash: metafile.download.hash,
+			client: side != "server",
+			server: side != "client",
+		});
+	}
+	Ok(files)
+}
+
+/// Reads the files expected by a `modrinth.index.json`, `.mrpack` or packwiz `pack.toml` file
+pub fn read_manifest(path: &Path) -> Result<Vec<ExpectedFile>> {
+	let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+	match extension {
+		"mrpack" => {
+			let mut zip = ZipArchive::new(fs::File::open(path)?)?;
+			let mut contents = String::new();
+			zip.by_name("modrinth.index.json")
+				.context("The .mrpack file doesn't contain a modrinth.index.json")?
+				.read_to_string(&mut contents)?;
+			Ok(read_modrinth_index(serde_json::from_str(&contents)?))
+		}
+		"json" => {
+			let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+			Ok(read_modrinth_index(
+				serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?,
+			))
+		}
+		"toml" => read_packwiz_pack(path),
+		_ => anyhow::bail!(
+			"Unknown manifest type {}, expected a modrinth.index.json, .mrpack or pack.toml file",
+			path.display()
+		),
+	}
+}
+
+/// Checks that the jars in the current folder match the files of a Modrinth or packwiz modpack
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct VerifyPackCommand {
+	/// The modrinth.index.json, .mrpack or packwiz pack.toml file of the pack
+	#[clap(long)]
+	manifest: PathBuf,
+	/// Only expect the files that are used on this side
+	#[clap(long, possible_values = &["client", "server"])]
+	side: Option<String>,
+	/// The folder of the pack that the current folder is
+	#[clap(long, default_value = "mods")]
+	folder: String,
+}
+
+impl JarCommand for VerifyPackCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("verify-pack")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let failed_jars = context.failed_jars;
+		let prefix = format!("{}/", self.folder.trim_end_matches('/'));
+		let expected: BTreeMap<String, ExpectedFile> = read_manifest(&self.manifest)?
+			.into_iter()
+			.filter(|file| match self.side.as_deref() {
+				Some("client") => file.client,
+				Some("server") => file.server,
+				_ => true,
+			})
+			.filter_map(|file| Some((file.path.strip_prefix(&prefix)?.to_owned(), file)))
+			.collect();
+		let local: BTreeMap<&str, &Path> = processed_jars
+			.iter()
+			.map(|jar| jar.0.as_path())
+			.chain(failed_jars.iter().map(PathBuf::as_path))
+			.map(|path| (file_name(path), path))
+			.collect();
+
+		let results: Vec<(&str, Result<Option<String>>)> = expected
+			.par_iter()
+			.filter_map(|(name, file)| {
+				let path = local.get(name.as_str())?;
+				if !file.can_check_hash() {
+					return Some((name.as_str(), Ok(None)));
+				}
+				let result = std::fs::read(path).map(|contents| {
+					let actual = hash::hash(&file.algorithm, &contents);
+					(!actual.eq_ignore_ascii_case(&file.hash)).then_some(actual)
+				});
+				Some((name.as_str(), result.map_err(Into::into)))
+			})
+			.collect();
+
+		let mut problems = 0;
+		let mut matching = 0;
+		let mut unchecked = vec![];
+		for (name, result) in results {
+			match result? {
+				Some(actual) => {
+					problems += 1;
+					let file = &expected[name];
+					println!(
+						"{}",
+						color::red(&format!(
+							"Modified: {} ({} {} expected, found {})",
+							name, file.algorithm, file.hash, actual
+						))
+					);
+				}
+				None if expected[name].can_check_hash() => matching += 1,
+				None => unchecked.push(name),
+			}
+		}
+		for name in expected.keys().filter(|name| !local.contains_key(name.as_str())) {
+			problems += 1;
+			println!("{}", color::red(&format!("Missing: {}", name)));
+		}
+		for name in local.keys().filter(|name| !expected.contains_key(**name)) {
+			problems += 1;
+			println!("{}", color::yellow(&format!("Extra: {}", name)));
+		}
+		for name in &unchecked {
+			println!("Unchecked: {} ({} hashes aren't supported)", name, expected[*name].algorithm);
+		}
+		println!("{} files match the manifest, {} problems found", matching, problems);
+		if problems > 0 {
+			process::exit(EXIT_PACK_MISMATCH);
+		}
+
+		Ok(())
+	}
+}
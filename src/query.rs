@@ -0,0 +1,405 @@
+//! A small expression language for filtering mods by their metadata, such as
+//! `environment == client && mixins > 0` or `depends contains cloth-config`
+
+use crate::{file_name, version, walk_jars, Environment, JarCommand, RunContext, TraversedJar, EXIT_NO_MATCHES};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{cmp::Ordering, collections::BTreeMap, path::PathBuf, process};
+
+/// The fields that can be used in a query, with descriptions for the help text and errors
+pub const FIELDS: &[(&str, &str)] = &[
+	("id", "mod id"),
+	("name", "mod name, or the id if it has no name"),
+	(
+		"version",
+		"mod version; <, <=, > and >= compare versions the way Fabric Loader does",
+	),
+	("environment", "client, server or both"),
+	("file", "file name of the jar"),
+	("nested", "whether the mod is bundled inside another jar"),
+	("size", "size of the jar in bytes"),
+	("mixins", "mixin classes"),
+	("mixin_plugins", "mixin config plugin classes"),
+	("depends", "ids of the required mods"),
+	("recommends", "ids of the recommended mods"),
+	("breaks", "ids of the mods it doesn't work with"),
+	("provides", "ids of the mods it can be used in place of"),
+	("jars", "file names of the nested jars"),
+	("authors", "author names"),
+	("licenses", "license identifiers"),
+	("adapters", "language adapters used by its entrypoints"),
+	("badges", "Mod Menu badges"),
+	("access_widener", "whether it has an access widener"),
+	("class_version", "highest class file major version, e.g. 61 for Java 17"),
+	(
+		"schema_version",
+		"schemaVersion of the fabric.mod.json, 0 for the oldest mods",
+	),
+];
+
+/// The value of a field for a mod
+pub enum Value {
+	Str(String),
+	Num(u64),
+	Bool(bool),
+	List(Vec<String>),
+	/// A field that the mod doesn't have, which doesn't match any comparison
+	Missing,
+}
+
+impl Value {
+	fn is_truthy(&self) -> bool {
+		match self {
+			Value::Str(value) => !value.is_empty(),
+			Value::Num(value) => *value != 0,
+			Value::Bool(value) => *value,
+			Value::List(values) => !values.is_empty(),
+			Value::Missing => false,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+	Eq,
+	Ne,
+	Lt,
+	Le,
+	Gt,
+	Ge,
+	Contains,
+}
+
+impl Operator {
+	fn test(self, ordering: Ordering) -> bool {
+		match self {
+			Operator::Eq => ordering == Ordering::Equal,
+			Operator::Ne => ordering != Ordering::Equal,
+			Operator::Lt => ordering == Ordering::Less,
+			Operator::Le => ordering != Ordering::Greater,
+			Operator::Gt => ordering == Ordering::Greater,
+			Operator::Ge => ordering != Ordering::Less,
+			Operator::Contains => unreachable!("contains isn't an ordering"),
+		}
+	}
+}
+
+enum Expr {
+	And(Box<Expr>, Box<Expr>),
+	Or(Box<Expr>, Box<Expr>),
+	Not(Box<Expr>),
+	/// A bare field, which matches if it is true, non-zero or non-empty
+	Field(String),
+	Compare(String, Operator, String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+	/// A field name or a value; values containing spaces or operators can be quoted
+	Word(String),
+	Operator(Operator),
+	And,
+	Or,
+	Not,
+	Open,
+	Close,
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>> {
+	let mut tokens = vec![];
+	let mut chars = query.chars().peekable();
+	while let Some(&c) = chars.peek() {
+		if c.is_whitespace() {
+			chars.next();
+			continue;
+		}
+		let two: String = chars.clone().take(2).collect();
+		let token = match two.as_str() {
+			"==" => Some(Token::Operator(Operator::Eq)),
+			"!=" => Some(Token::Operator(Operator::Ne)),
+			"<=" => Some(Token::Operator(Operator::Le)),
+			">=" => Some(Token::Operator(Operator::Ge)),
+			"&&" => Some(Token::And),
+			"||" => Some(Token::Or),
+			_ => None,
+		};
+		if let Some(token) = token {
+			chars.nth(1);
+			tokens.push(token);
+			continue;
+		}
+		chars.next();
+		tokens.push(match c {
+			'<' => Token::Operator(Operator::Lt),
+			'>' => Token::Operator(Operator::Gt),
+			'!' => Token::Not,
+			'(' => Token::Open,
+			')' => Token::Close,
+			'"' | '\'' => {
+				let mut word = String::new();
+				loop {
+					match chars.next() {
+						Some(next) if next == c => break,
+						Some(next) => word.push(next),
+						None => anyhow::bail!("The string {}{} is missing a closing quote", c, word),
+					}
+				}
+				Token::Word(word)
+			}
+			'=' | '&' | '|' => anyhow::bail!("Unknown operator {}, expected ==, &&, or ||", c),
+			_ => {
+				let mut word = c.to_string();
+				while let Some(&next) = chars.peek() {
+					if next.is_whitespace() || "()!=<>&|\"'".contains(next) {
+						break;
+					}
+					word.push(next);
+					chars.next();
+				}
+				match word.as_str() {
+					"contains" => Token::Operator(Operator::Contains),
+					"and" => Token::And,
+					"or" => Token::Or,
+					"not" => Token::Not,
+					_ => Token::Word(word),
+				}
+			}
+		});
+	}
+	Ok(tokens)
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	pos: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.pos)
+	}
+
+	fn next(&mut self) -> Option<&Token> {
+		self.pos += 1;
+		self.tokens.get(self.pos - 1)
+	}
+
+	fn or(&mut self) -> Result<Expr> {
+		let mut expr = self.and()?;
+		while self.peek() == Some(&Token::Or) {
+			self.pos += 1;
+			expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+		}
+		Ok(expr)
+	}
+
+	fn and(&mut self) -> Result<Expr> {
+		let mut expr = self.unary()?;
+		while self.peek() == Some(&Token::And) {
+			self.pos += 1;
+			expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+		}
+		Ok(expr)
+	}
+
+	fn unary(&mut self) -> Result<Expr> {
+		match self.next() {
+			Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+			Some(Token::Open) => {
+				let expr = self.or()?;
+				if self.next() != Some(&Token::Close) {
+					anyhow::bail!("Missing a closing parenthesis");
+				}
+				Ok(expr)
+			}
+			Some(Token::Word(field)) => {
+				let field = field.clone();
+				if !FIELDS.iter().any(|(name, _)| *name == field) {
+					let names: Vec<&str> = FIELDS.iter().map(|(name, _)| *name).collect();
+					anyhow::bail!("Unknown field {}, expected one of {}", field, names.join(", "));
+				}
+				let operator = match self.peek() {
+					Some(Token::Operator(operator)) => *operator,
+					_ => return Ok(Expr::Field(field)),
+				};
+				self.pos += 1;
+				match self.next() {
+					Some(Token::Word(value)) => Ok(Expr::Compare(field, operator, value.clone())),
+					_ => anyhow::bail!("Expected a value to compare {} with", field),
+				}
+			}
+			Some(token) => anyhow::bail!("Expected a field name, found {:?}", token),
+			None => anyhow::bail!("The query ended early, expected a field name"),
+		}
+	}
+}
+
+fn parse_number(field: &str, value: &str) -> Result<u64> {
+	value
+		.parse()
+		.map_err(|_| anyhow::anyhow!("{} is compared with {}, which isn't a number", field, value))
+}
+
+fn compare(field: &str, actual: &Value, operator: Operator, expected: &str) -> Result<bool> {
+	Ok(match (actual, operator) {
+		(Value::Missing, _) => false,
+		(Value::Str(actual), Operator::Contains) => actual.to_lowercase().contains(&expected.to_lowercase()),
+		(Value::Str(actual), Operator::Eq | Operator::Ne) => operator.test(actual.as_str().cmp(expected)),
+		(Value::Str(actual), _) => operator.test(version::compare(actual, expected)),
+		(Value::List(values), Operator::Contains) => values.iter().any(|value| value == expected),
+		// Lists are compared by their length, so that `mixins > 0` matches mods with mixins
+		(Value::List(values), _) => operator.test((values.len() as u64).cmp(&parse_number(field, expected)?)),
+		(Value::Num(_), Operator::Contains) => anyhow::bail!("{} is a number, which can't contain anything", field),
+		(Value::Num(actual), _) => operator.test(actual.cmp(&parse_number(field, expected)?)),
+		(Value::Bool(actual), Operator::Eq | Operator::Ne) => match expected {
+			"true" | "false" => operator.test(actual.cmp(&(expected == "true"))),
+			_ => anyhow::bail!("{} can only be compared with true or false", field),
+		},
+		(Value::Bool(_), _) => anyhow::bail!("{} can only be compared with == or !=", field),
+	})
+}
+
+/// A parsed query
+pub struct Query(Expr);
+
+impl Query {
+	pub fn parse(query: &str) -> Result<Query> {
+		let mut parser = Parser {
+			tokens: tokenize(query)?,
+			pos: 0,
+		};
+		let expr = parser.or()?;
+		if let Some(token) = parser.peek() {
+			anyhow::bail!("Unexpected {:?} after the end of the query", token);
+		}
+		Ok(Query(expr))
+	}
+
+	/// Whether the mod with these fields (which must include every field in [FIELDS]) matches the query
+	pub fn matches(&self, fields: &BTreeMap<&str, Value>) -> Result<bool> {
+		fn eval(expr: &Expr, fields: &BTreeMap<&str, Value>) -> Result<bool> {
+			Ok(match expr {
+				Expr::And(a, b) => eval(a, fields)? && eval(b, fields)?,
+				Expr::Or(a, b) => eval(a, fields)? || eval(b, fields)?,
+				Expr::Not(expr) => !eval(expr, fields)?,
+				Expr::Field(field) => fields[field.as_str()].is_truthy(),
+				Expr::Compare(field, operator, expected) => compare(field, &fields[field.as_str()], *operator, expected)?,
+			})
+		}
+		eval(&self.0, fields)
+	}
+}
+
+/// Lists the mods in the current folder (including nested mods) that match a query, such as
+/// `environment == client && mixins > 0` or `depends contains cloth-config`
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct QueryCommand {
+	/// The query: comparisons of a field with a value using ==, !=, <, <=, >, >= or contains, combined with &&, || and
+	/// ! (or and, or and not) and parentheses. Lists are compared by their length. The fields are id, name, version,
+	/// environment, file, nested, size, mixins, mixin_plugins, depends, recommends, breaks, provides, jars, authors,
+	/// licenses, adapters, badges, access_widener, class_version and schema_version.
+	pub query: String,
+}
+
+impl JarCommand for QueryCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let query = Query::parse(&self.query)?;
+		let mut matching = vec![];
+		let mut result = Ok(());
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, nested_file_name, parent| {
+				if result.is_err() {
+					return;
+				}
+				if let TraversedJar::FabricJar {
+					mod_name,
+					mod_id,
+					mod_version,
+					size,
+					environment,
+					mixins,
+					mixin_config_plugins,
+					contained_jars,
+					access_widener_contents,
+					depends,
+					recommends,
+					breaks,
+					provides,
+					licenses,
+					authors,
+					class_version,
+					adapters,
+					mod_menu,
+					schema_version,
+					..
+				} = nested
+				{
+					use Value;
+					let ids = |map: &BTreeMap<String, Vec<String>>| Value::List(map.keys().cloned().collect());
+					let fields: BTreeMap<&str, Value> = vec![
+						("id", Value::Str(mod_id.clone())),
+						("name", Value::Str(mod_name.clone().unwrap_or_else(|| mod_id.clone()))),
+						("version", Value::Str(mod_version.clone())),
+						(
+							"environment",
+							Value::Str(
+								match environment {
+									Environment::Both => "both",
+									Environment::Client => "client",
+									Environment::Server => "server",
+								}
+								.to_owned(),
+							),
+						),
+						("file", Value::Str(nested_file_name.to_owned())),
+						("nested", Value::Bool(parent.is_some())),
+						("size", Value::Num(*size)),
+						("mixins", Value::List(mixins.values().flatten().cloned().collect())),
+						("mixin_plugins", Value::List(mixin_config_plugins.clone())),
+						("depends", ids(depends)),
+						("recommends", ids(recommends)),
+						("breaks", ids(breaks)),
+						("provides", Value::List(provides.clone())),
+						("jars", Value::List(contained_jars.keys().cloned().collect())),
+						("authors", Value::List(authors.clone())),
+						("licenses", Value::List(licenses.clone())),
+						("adapters", Value::List(adapters.iter().cloned().collect())),
+						("badges", Value::List(mod_menu.badges.clone())),
+						("access_widener", Value::Bool(access_widener_contents.is_some())),
+						("schema_version", Value::Num(*schema_version)),
+						(
+							"class_version",
+							class_version.map_or(Value::Missing, |class_version| Value::Num(class_version.into())),
+						),
+					]
+					.into_iter()
+					.collect();
+					match query.matches(&fields) {
+						Ok(true) => matching.push(match parent {
+							Some(_) => format!("{} {} ({} > {})", mod_id, mod_version, file_name(&jar.0), nested_file_name),
+							None => format!("{} {} ({})", mod_id, mod_version, nested_file_name),
+						}),
+						Ok(false) => {}
+						Err(err) => result = Err(err),
+					}
+				}
+			});
+		}
+		result?;
+
+		if matching.is_empty() {
+			println!("No mods matching the query found!");
+			process::exit(EXIT_NO_MATCHES);
+		}
+		for line in matching {
+			println!("{}", line);
+		}
+
+		Ok(())
+	}
+}
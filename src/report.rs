@@ -0,0 +1,231 @@
+use crate::{
+	dependency_problems, environment_report, file_name, loader_mod_ids, loader_names, top_level_providers, Environment,
+	JarCommand, RunContext, TraversedJar,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	fmt::Write as _,
+	fs,
+	path::{Path, PathBuf},
+};
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+details { margin: 0.3em 0; }
+details details { margin-left: 1.5em; }
+summary { cursor: pointer; }
+.file { color: #666; }
+.warning { color: #b00; }
+pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }
+table { border-collapse: collapse; }
+td { padding: 0.1em 1em 0.1em 0; vertical-align: top; }
+#search { width: 30em; padding: 0.3em; margin-bottom: 1em; }";
+
+const SCRIPT: &str = "document.getElementById('search').addEventListener('input', function (e) {
+	var query = e.target.value.toLowerCase();
+	document.querySelectorAll('details.jar').forEach(function (jar) {
+		var matches = jar.textContent.toLowerCase().indexOf(query) !== -1;
+		jar.style.display = matches ? '' : 'none';
+		if (query && matches) jar.open = true;
+	});
+});";
+
+fn escape(s: &str) -> String {
+	s.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+fn write_list(out: &mut String, title: &str, lines: &[String], empty_message: &str) -> Result<()> {
+	writeln!(
+		out,
+		"<details open><summary><h2 style=\"display: inline\">{}</h2></summary>",
+		escape(title)
+	)?;
+	if lines.is_empty() {
+		writeln!(out, "<p>{}</p>", escape(empty_message))?;
+	} else {
+		writeln!(out, "<ul>")?;
+		for line in lines {
+			writeln!(out, "<li class=\"warning\">{}</li>", escape(line))?;
+		}
+		writeln!(out, "</ul>")?;
+	}
+	writeln!(out, "</details>")?;
+	Ok(())
+}
+
+fn write_jar(out: &mut String, jar: &TraversedJar, file_name: &str, top_level: bool) -> Result<()> {
+	let class = if top_level { " class=\"jar\"" } else { "" };
+	match jar {
+		TraversedJar::NonMod => {
+			writeln!(
+				out,
+				"<details{}><summary>{} <span class=\"file\">(Not a mod)</span></summary></details>",
+				class,
+				escape(file_name)
+			)?;
+		}
+		TraversedJar::OtherLoaderJar { mods, .. } => {
+			writeln!(
+				out,
+				"<details{}><summary>{} <span class=\"file\">({}, {})</span></summary></details>",
+				class,
+				escape(&loader_mod_ids(mods)),
+				escape(file_name),
+				loader_names(mods)
+			)?;
+		}
+		TraversedJar::FabricJar {
+			mod_name,
+			mod_id,
+			mod_version,
+			hash,
+			environment,
+			mixins,
+			mixin_config_plugins,
+			contained_jars,
+			access_widener_contents,
+			depends,
+			recommends,
+			breaks,
+			..
+		} => {
+			writeln!(
+				out,
+				"<details{}><summary>{} {} <span class=\"file\">({})</span></summary>",
+				class,
+				escape(mod_name.as_ref().unwrap_or(mod_id)),
+				escape(mod_version),
+				escape(file_name)
+			)?;
+			writeln!(out, "<table>")?;
+			writeln!(out, "<tr><td>Id</td><td>{}</td></tr>", escape(mod_id))?;
+			writeln!(out, "<tr><td>Environment</td><td>{}</td></tr>", environment)?;
+			writeln!(out, "<tr><td>SHA-1</td><td>{}</td></tr>", hash)?;
+			for (kind, dependencies) in [("Depends", depends), ("Recommends", recommends), ("Breaks", breaks)] {
+				if !dependencies.is_empty() {
+					let dependencies: Vec<String> = dependencies
+						.iter()
+						.map(|(id, ranges)| format!("{} {}", id, ranges.join(" || ")))
+						.collect();
+					writeln!(out, "<tr><td>{}</td><td>{}</td></tr>", kind, escape(&dependencies.join(", ")))?;
+				}
+			}
+			if !mixin_config_plugins.is_empty() {
+				writeln!(
+					out,
+					"<tr><td>Mixin plugins</td><td>{}</td></tr>",
+					escape(&mixin_config_plugins.join(", "))
+				)?;
+			}
+			writeln!(out, "</table>")?;
+
+			let mixin_count: usize = mixins.values().map(Vec::len).sum();
+			if mixin_count > 0 {
+				writeln!(out, "<details><summary>Mixins ({})</summary>", mixin_count)?;
+				for (env, env_mixins) in mixins.iter().filter(|(_, env_mixins)| !env_mixins.is_empty()) {
+					let title = match env {
+						Environment::Both => "Common",
+						Environment::Client => "Client",
+						Environment::Server => "Server",
+					};
+					writeln!(out, "<p>{}:</p><ul>", title)?;
+					for mixin in env_mixins {
+						writeln!(out, "<li>{}</li>", escape(mixin))?;
+					}
+					writeln!(out, "</ul>")?;
+				}
+				writeln!(out, "</details>")?;
+			}
+
+			if let Some(contents) = access_widener_contents {
+				writeln!(
+					out,
+					"<details><summary>Access widener</summary><pre>{}</pre></details>",
+					escape(contents)
+				)?;
+			}
+
+			if !contained_jars.is_empty() {
+				writeln!(out, "<details open><summary>Nested jars ({})</summary>", contained_jars.len())?;
+				for (name, contained_jar) in contained_jars {
+					write_jar(out, contained_jar, name, false)?;
+				}
+				writeln!(out, "</details>")?;
+			}
+			writeln!(out, "</details>")?;
+		}
+	}
+	Ok(())
+}
+
+/// Writes a single self-contained HTML page describing every jar in `jars` to `out`
+pub fn write_html(out: &Path, jars: &[(PathBuf, TraversedJar)]) -> Result<()> {
+	let mut html = String::new();
+	writeln!(html, "<!DOCTYPE html>")?;
+	writeln!(html, "<html><head><meta charset=\"utf-8\"><title>Mod report</title>")?;
+	writeln!(html, "<style>{}</style></head><body>", STYLE)?;
+	writeln!(html, "<h1>Mod report</h1>")?;
+
+	let problems: Vec<String> = dependency_problems(jars).into_iter().collect();
+	write_list(&mut html, "Dependency issues", &problems, "No dependency problems found")?;
+	let duplicates: Vec<String> = top_level_providers(jars)
+		.into_iter()
+		.filter(|(_, files)| files.len() > 1)
+		.map(|(mod_id, files)| {
+			format!(
+				"{} is supplied by multiple jars: {}",
+				mod_id,
+				files.into_iter().collect::<Vec<_>>().join(", ")
+			)
+		})
+		.collect();
+	write_list(&mut html, "Duplicate mods", &duplicates, "No duplicate mods found")?;
+
+	writeln!(
+		html,
+		"<details><summary><h2 style=\"display: inline\">Environments</h2></summary><pre>"
+	)?;
+	for line in environment_report(jars, None).0 {
+		writeln!(html, "{}", escape(&line))?;
+	}
+	writeln!(html, "</pre></details>")?;
+
+	writeln!(html, "<h2>Jars ({})</h2>", jars.len())?;
+	writeln!(
+		html,
+		"<input id=\"search\" type=\"search\" placeholder=\"Search mods, mixins, access wideners...\">"
+	)?;
+	for jar in jars {
+		write_jar(&mut html, &jar.1, file_name(&jar.0), true)?;
+	}
+
+	writeln!(html, "<script>{}</script>", SCRIPT)?;
+	writeln!(html, "</body></html>")?;
+	fs::write(out, html)?;
+	Ok(())
+}
+
+/// Writes a self-contained HTML report of the mods in the current folder, for sharing with others
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct ReportCommand {
+	/// The HTML file to write the report to
+	#[clap(long)]
+	html: PathBuf,
+}
+
+impl JarCommand for ReportCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		write_html(&self.html, &processed_jars)?;
+		println!("Wrote report for {} jars to {}", processed_jars.len(), self.html.display());
+
+		Ok(())
+	}
+}
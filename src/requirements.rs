@@ -0,0 +1,272 @@
+//! Listing the Minecraft, loader and Java versions that mods need
+
+use crate::{file_name, java_release, version, walk_jars, DetailLevel, JarCommand, RunContext, TraversedJar};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+};
+
+/// Prints the Minecraft versions accepted by mods in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct McVersionsCommand {
+	/// List the mods that don't accept this Minecraft version
+	#[clap(long)]
+	check: Option<String>,
+}
+
+impl JarCommand for McVersionsCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let check = match &self.check {
+			Some(check) => {
+				Some(version::Version::parse(check).ok_or_else(|| anyhow::anyhow!("{} is not a valid semantic version", check))?)
+			}
+			None => None,
+		};
+
+		let mut declared: BTreeMap<(&str, &[String]), BTreeSet<&str>> = BTreeMap::new();
+		let mut undeclared: BTreeSet<&str> = BTreeSet::new();
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, _| {
+				if let TraversedJar::FabricJar { mod_id, depends, .. } = nested {
+					match depends.get("minecraft") {
+						Some(ranges) => {
+							declared.entry((mod_id, ranges)).or_default().insert(file_name);
+						}
+						None => {
+							undeclared.insert(mod_id);
+						}
+					}
+				}
+			});
+		}
+
+		let mut intersection = version::VersionRange::any();
+		let mut rejecting = vec![];
+		for ((mod_id, ranges), file_names) in &declared {
+			let file_names = file_names.iter().cloned().collect::<Vec<_>>().join(", ");
+			match version::VersionRange::parse_any(ranges) {
+				Some(range) => {
+					println!("{} ({}): {}", mod_id, file_names, ranges.join(" || "));
+					if let Some(ref check) = check {
+						if !range.contains(check) {
+							rejecting.push(*mod_id);
+						}
+					}
+					intersection = intersection.intersect(&range);
+				}
+				None => println!("{} ({}): {} (couldn't be parsed)", mod_id, file_names, ranges.join(" || ")),
+			}
+		}
+		if !undeclared.is_empty() {
+			println!(
+				"No Minecraft version declared: {}",
+				undeclared.iter().cloned().collect::<Vec<_>>().join(", ")
+			);
+		}
+
+		if declared.is_empty() {
+			println!("No mods declaring a Minecraft version found!");
+		} else if intersection.is_empty() {
+			println!("No Minecraft version is accepted by every mod!");
+		} else {
+			println!("Accepted by every mod: {}", intersection);
+		}
+		if let Some(ref check_string) = self.check {
+			if rejecting.is_empty() {
+				println!("Every mod accepts {}", check_string);
+			} else {
+				println!("Mods that don't accept {}: {}", check_string, rejecting.join(", "));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Computes the Fabric Loader and Fabric API versions required by mods in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct LoaderReqCommand {
+	/// Also list the version range each mod requires
+	#[clap(short, long)]
+	verbose: bool,
+}
+
+impl JarCommand for LoaderReqCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let mut requirements: Vec<(String, Vec<&str>)> = vec![
+			("Fabric Loader".to_owned(), vec!["fabricloader"]),
+			("Fabric API".to_owned(), vec!["fabric", "fabric-api"]),
+		];
+		// Individual Fabric API modules can be depended on (and bundled) separately from the whole API
+		let mut modules = BTreeSet::new();
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, _, _| {
+				if let TraversedJar::FabricJar { depends, .. } = nested {
+					modules.extend(
+						depends
+							.keys()
+							.map(String::as_str)
+							.filter(|id| id.starts_with("fabric-") && *id != "fabric-api" && !id.starts_with("fabric-language-")),
+					);
+				}
+			});
+		}
+		for module in modules {
+			requirements.push((format!("Fabric API module {}", module), vec![module]));
+		}
+
+		for (name, ids) in &requirements {
+			let mut declared: BTreeMap<(&str, &str, &[String]), BTreeSet<&str>> = BTreeMap::new();
+			let mut installed = BTreeSet::new();
+			for jar in &processed_jars {
+				walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, _| {
+					if let TraversedJar::FabricJar {
+						mod_id,
+						mod_version,
+						depends,
+						..
+					} = nested
+					{
+						if ids.contains(&mod_id.as_str()) {
+							installed.insert((mod_id.as_str(), mod_version.as_str(), file_name));
+						}
+						for id in ids.iter() {
+							if let Some(ranges) = depends.get(*id) {
+								declared.entry((mod_id, id, ranges)).or_default().insert(file_name);
+							}
+						}
+					}
+				});
+			}
+
+			println!("{}:", name);
+			let mut required = version::VersionRange::any();
+			for ((mod_id, id, ranges), file_names) in &declared {
+				let range = version::VersionRange::parse_any(ranges);
+				if self.verbose || range.is_none() {
+					println!(
+						"    {} ({}) requires {} {}{}",
+						mod_id,
+						file_names.iter().cloned().collect::<Vec<_>>().join(", "),
+						id,
+						ranges.join(" || "),
+						if range.is_none() { " (couldn't be parsed)" } else { "" }
+					);
+				}
+				if let Some(range) = range {
+					required = required.intersect(&range);
+				}
+			}
+
+			if declared.is_empty() {
+				println!("    Not required by any mod");
+			} else if required.is_empty() {
+				println!("    No version satisfies every mod!");
+			} else {
+				match required.minimum() {
+					Some(minimum) if minimum.inclusive => println!("    Minimum version: {}", minimum.version),
+					Some(minimum) => println!("    Minimum version: newer than {}", minimum.version),
+					None => println!("    Minimum version: any"),
+				}
+				println!("    Accepted versions: {}", required);
+			}
+			for (mod_id, mod_version, file_name) in &installed {
+				let satisfied = version::Version::parse(mod_version).map(|v| required.contains(&v));
+				println!(
+					"    Found {} {} ({}){}",
+					mod_id,
+					mod_version,
+					file_name,
+					match satisfied {
+						Some(true) => "",
+						Some(false) => " which doesn't satisfy every mod!",
+						None => " which has an invalid version",
+					}
+				);
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Reports the Java version required by mods in the current folder, based on their class file versions
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct JavaVersionCommand {}
+
+impl JarCommand for JavaVersionCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		let mut required: Option<(u16, &str)> = None;
+		for jar in &processed_jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, parent| {
+				if let TraversedJar::FabricJar {
+					mod_name,
+					mod_id,
+					class_version,
+					..
+				} = nested
+				{
+					let indent = if parent.is_some() { "    " } else { "" };
+					match class_version {
+						Some(class_version) => {
+							println!(
+								"{}{} ({}): Java {} (class file version {})",
+								indent,
+								mod_name.as_ref().unwrap_or(mod_id),
+								file_name,
+								java_release(*class_version),
+								class_version
+							);
+							if required.is_none_or(|(version, _)| *class_version > version) {
+								required = Some((*class_version, mod_id));
+							}
+						}
+						None => println!(
+							"{}{} ({}): no classes",
+							indent,
+							mod_name.as_ref().unwrap_or(mod_id),
+							file_name
+						),
+					}
+				}
+			});
+		}
+
+		match required {
+			Some((class_version, mod_id)) => println!(
+				"Java {} or newer is required (class file version {}, required by {})",
+				java_release(class_version),
+				class_version,
+				mod_id
+			),
+			None => println!("No classes found!"),
+		}
+
+		Ok(())
+	}
+}
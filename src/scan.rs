@@ -0,0 +1,327 @@
+use crate::{
+	classfile::ConstantPool,
+	display_name, lint,
+	lint::{Problem, Severity},
+	read_mixin_config, read_mod_json, read_nested_jar, JarCommand, JarContents, RunContext, ScanOptions, TraversalBudget,
+	TraversedJar, EXIT_MALWARE, EXIT_UNREADABLE_JARS,
+};
+use anyhow::{Context, Result};
+use clap::{AppSettings, Clap};
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs,
+	io::{Cursor, Read, Seek},
+	path::{Path, PathBuf},
+	process,
+};
+use zip::ZipArchive;
+
+/// Indicators of known malware, matched against file names, class names and constants. These are from the
+/// fractureiser incident of June 2023.
+const BUILTIN_SIGNATURES: &[(&str, &[&str])] = &[(
+	"fractureiser",
+	&[
+		"dev/neko/nekoclient",
+		"dev/neko/nekoinjector",
+		"85.217.144.130",
+		"107.189.3.101",
+		"files-8ie.pages.dev",
+		"skyrage.de",
+	],
+)];
+
+/// Classes whose use is worth a look in a mod, as they can run programs or load code from outside the jar
+const SUSPICIOUS_CLASSES: &[(&str, &str)] = &[
+	("java/lang/ProcessBuilder", "ProcessBuilder"),
+	("java/net/URLClassLoader", "URLClassLoader"),
+];
+
+/// A set of patterns that identify a piece of malware
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Signature {
+	name: String,
+	/// Substrings of file names, class names or constants that indicate the malware
+	patterns: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignatureFile {
+	#[serde(default)]
+	signatures: Vec<Signature>,
+}
+
+/// The built-in signatures, followed by those in the given TOML files
+pub fn load_signatures(files: &[impl AsRef<Path>]) -> Result<Vec<Signature>> {
+	let mut signatures: Vec<Signature> = BUILTIN_SIGNATURES
+		.iter()
+		.map(|(name, patterns)| Signature {
+			name: (*name).to_owned(),
+			patterns: patterns.iter().map(|pattern| (*pattern).to_owned()).collect(),
+		})
+		.collect();
+	for file in files {
+		let file = file.as_ref();
+		let contents = fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+		let signature_file: SignatureFile =
+			toml::from_str(&contents).with_context(|| format!("Failed to parse {}", file.display()))?;
+		signatures.extend(signature_file.signatures);
+	}
+	Ok(signatures)
+}
+
+/// Classes that were flagged for one reason, with up to a few examples
+#[derive(Default)]
+struct Findings {
+	count: usize,
+	examples: BTreeSet<String>,
+}
+
+const MAX_EXAMPLES: usize = 3;
+
+impl Findings {
+	fn add(&mut self, class: &str) {
+		self.count += 1;
+		if self.examples.len() < MAX_EXAMPLES {
+			self.examples.insert(class.to_owned());
+		}
+	}
+
+	fn describe(&self, what: &str) -> String {
+		let examples = self.examples.iter().cloned().collect::<Vec<_>>().join(", ");
+		if self.count == 1 {
+			format!("{} in 1 class ({})", what, examples)
+		} else {
+			format!("{} in {} classes (e.g. {})", what, self.count, examples)
+		}
+	}
+}
+
+/// Whether a class name looks like the output of an obfuscator, e.g. `a/b/c` or `ab`
+fn is_obfuscated(class: &str) -> bool {
+	let simple_name = class.rsplit('/').next().unwrap_or(class);
+	let outer_name = simple_name.split('$').next().unwrap_or(simple_name);
+	outer_name.len() <= 2 && outer_name.chars().all(|c| c.is_ascii_lowercase())
+}
+
+/// The packages (as `/`-separated prefixes) of a Fabric mod's entrypoints and mixins, shortened to at most three
+/// segments so that the rest of the mod's classes fall under them
+fn declared_packages<R: Read + Seek>(zip: &mut ZipArchive<R>) -> BTreeSet<String> {
+	let fabric_mod_json = match read_mod_json(zip) {
+		Ok(fabric_mod_json) => fabric_mod_json,
+		Err(_) => return BTreeSet::new(),
+	};
+	let mut packages: Vec<String> = fabric_mod_json
+		.entrypoints
+		.values()
+		.flatten()
+		.filter_map(|entrypoint| {
+			let class = entrypoint.value().split("::").next().unwrap_or_default();
+			class.rsplit_once('.').map(|(package, _)| package.to_owned())
+		})
+		.collect();
+	for mixin_entry in &fabric_mod_json.mixins {
+		if let Ok(config) = read_mixin_config(zip, mixin_entry.config()) {
+			packages.extend(config.package);
+		}
+	}
+	packages
+		.iter()
+		.map(|package| package.split('.').take(3).collect::<Vec<_>>().join("/") + "/")
+		.collect()
+}
+
+/// Looks for signs of malware in a jar and the jars nested in it
+fn scan_archive<R: Read + Seek>(
+	zip: &mut ZipArchive<R>, signatures: &[Signature], budget: &mut TraversalBudget, problems: &mut Vec<Problem>, prefix: &str,
+) -> Result<()> {
+	let packages = declared_packages(zip);
+	let names: Vec<String> = zip.file_names().map(str::to_owned).collect();
+
+	let mut signature_matches: BTreeMap<(&str, &str), Findings> = BTreeMap::new();
+	let mut suspicious_uses: BTreeMap<&str, Findings> = BTreeMap::new();
+	let mut outside_packages = Findings::default();
+	let mut obfuscated = Findings::default();
+	let mut classes = 0;
+
+	for name in &names {
+		for signature in signatures {
+			for pattern in signature.patterns.iter().filter(|pattern| name.contains(pattern.as_str())) {
+				signature_matches.entry((&signature.name, pattern)).or_default().add(name);
+			}
+		}
+
+		if let Some(class) = name.strip_suffix(".class") {
+			if class.ends_with("module-info") || class.ends_with("package-info") {
+				continue;
+			}
+			classes += 1;
+			let class = class.strip_prefix("META-INF/versions/").map_or(class, |versioned| {
+				versioned.split_once('/').map_or(versioned, |(_, class)| class)
+			});
+			if !packages.is_empty() && !packages.iter().any(|package| class.starts_with(package.as_str())) {
+				outside_packages.add(class);
+			}
+			if is_obfuscated(class) {
+				obfuscated.add(class);
+			}
+
+			let mut contents = vec![];
+			zip.by_name(name)?.read_to_end(&mut contents)?;
+			// Classes that can't be parsed can't be loaded either, so they aren't a threat on their own
+			let constant_pool = match ConstantPool::parse(&contents) {
+				Ok(constant_pool) => constant_pool,
+				Err(_) => continue,
+			};
+			for constant in constant_pool.utf8() {
+				for signature in signatures {
+					for pattern in signature
+						.patterns
+						.iter()
+						.filter(|pattern| constant.contains(pattern.as_str()))
+					{
+						signature_matches.entry((&signature.name, pattern)).or_default().add(class);
+					}
+				}
+			}
+			let referenced: BTreeSet<&str> = constant_pool.classes().collect();
+			for (suspicious_class, description) in SUSPICIOUS_CLASSES {
+				if referenced.contains(suspicious_class) {
+					suspicious_uses.entry(description).or_default().add(class);
+				}
+			}
+			if referenced.contains("java/lang/Runtime") && constant_pool.utf8().any(|constant| constant == "exec") {
+				suspicious_uses.entry("Runtime.exec").or_default().add(class);
+			}
+		} else if name.ends_with(".jar") {
+			if budget.ancestors.len() >= budget.limits.max_depth {
+				continue;
+			}
+			let contents = read_nested_jar(zip, name, budget)?;
+			let mut nested_zip = ZipArchive::new(Cursor::new(contents))?;
+			// Only the depth of the ancestors is used here, so their names stand in for their hashes
+			budget.ancestors.push(name.clone());
+			let result = scan_archive(
+				&mut nested_zip,
+				signatures,
+				budget,
+				problems,
+				&format!("{}{}: ", prefix, name),
+			);
+			budget.ancestors.pop();
+			result?;
+		}
+	}
+
+	for ((signature, pattern), findings) in signature_matches {
+		problems.push(Problem {
+			severity: Severity::Error,
+			message: format!(
+				"{}matches the {} signature: {}",
+				prefix,
+				signature,
+				findings.describe(pattern)
+			),
+		});
+	}
+	for (description, findings) in suspicious_uses {
+		problems.push(Problem {
+			severity: Severity::Warning,
+			message: format!("{}{}", prefix, findings.describe(&format!("uses {}", description))),
+		});
+	}
+	if outside_packages.count > 0 {
+		let packages: Vec<String> = packages
+			.iter()
+			.map(|package| package.trim_end_matches('/').replace('/', "."))
+			.collect();
+		problems.push(Problem {
+			severity: Severity::Warning,
+			message: format!(
+				"{}{}",
+				prefix,
+				outside_packages.describe(&format!("code outside of the mod's packages ({})", packages.join(", ")))
+			),
+		});
+	}
+	// Only obfuscated names among readable ones stand out; some libraries are obfuscated as a whole
+	if obfuscated.count > 0 && obfuscated.count * 2 < classes {
+		problems.push(Problem {
+			severity: Severity::Warning,
+			message: format!("{}{}", prefix, obfuscated.describe("obfuscated names")),
+		});
+	}
+	Ok(())
+}
+
+/// Looks for signs of malware in a jar: matches of known malware signatures, use of classes that can run programs or
+/// load code, code outside of the packages a mod declares, and obfuscated classes in a jar that otherwise isn't
+pub fn scan_jar(path: &Path, signatures: &[Signature], scan: &ScanOptions) -> Result<Vec<Problem>> {
+	let contents = JarContents::open(path, scan)?;
+	let mut zip = contents.archive()?;
+	let mut problems = vec![];
+	let mut budget = TraversalBudget::new(&scan.limits);
+	scan_archive(&mut zip, signatures, &mut budget, &mut problems, "")?;
+	Ok(problems)
+}
+
+/// Looks for signs of malware in the jars in the current folder, such as known malware signatures and code that runs
+/// programs or loads classes from elsewhere
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct ScanCommand {
+	/// TOML files with additional signatures to match, each a [[signatures]] table with a name and a list of patterns
+	#[clap(long, use_delimiter = true)]
+	signatures: Vec<PathBuf>,
+}
+
+impl JarCommand for ScanCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("scan")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		let signatures = load_signatures(&self.signatures)?;
+		let results: Vec<(String, Result<Vec<Problem>>)> = processed_jars
+			.par_iter()
+			.map(|jar| (display_name(&jar.0), scan_jar(&jar.0, &signatures, scan)))
+			.collect();
+
+		let mut errors = 0;
+		let mut warnings = 0;
+		let mut unreadable = false;
+		for (name, problems) in results {
+			let problems = match problems {
+				Ok(problems) if !problems.is_empty() => problems,
+				Ok(_) => continue,
+				Err(err) => {
+					eprintln!("Failed to scan {}: {}", name, err);
+					unreadable = true;
+					continue;
+				}
+			};
+			println!("{}:", name);
+			let (jar_errors, jar_warnings) = lint::print_problems(&problems);
+			errors += jar_errors;
+			warnings += jar_warnings;
+		}
+		if errors == 0 && warnings == 0 {
+			println!("Nothing suspicious found");
+		} else {
+			println!("{} signature matches, {} warnings", errors, warnings);
+			if errors > 0 {
+				process::exit(EXIT_MALWARE);
+			}
+		}
+		// Jars that couldn't be scanned may still contain malware
+		if unreadable {
+			process::exit(EXIT_UNREADABLE_JARS);
+		}
+
+		Ok(())
+	}
+}
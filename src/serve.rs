@@ -0,0 +1,149 @@
+use crate::{file_name, scan_dir, walk_archives, walk_jars, DetailLevel, ScanOptions, TraversedJar};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+};
+use tiny_http::{Header, Method, Response, Server};
+
+/// The traversal results of a folder, kept in memory between requests
+struct Inventory {
+	jars: Vec<(PathBuf, TraversedJar)>,
+	failed: Vec<PathBuf>,
+	/// Paths of class files, mapped to the chain of jar file names leading to each jar that contains them
+	classes: BTreeMap<String, Vec<Vec<String>>>,
+}
+
+impl Inventory {
+	fn scan(dir: &Path, scan: &ScanOptions, show_progress: bool) -> Result<Self> {
+		let scanned = scan_dir(dir, DetailLevel::Full, scan, show_progress)?;
+		let mut classes: BTreeMap<String, Vec<Vec<String>>> = BTreeMap::new();
+		for jar in &scanned.jars {
+			let mut path = vec![file_name(&jar.0).to_owned()];
+			let indexed = walk_archives(&jar.0, &mut path, scan, &mut |path, zip| {
+				for name in zip.file_names().filter(|name| name.ends_with(".class")) {
+					classes.entry(name.to_owned()).or_default().push(path.to_vec());
+				}
+				Ok(())
+			});
+			if let Err(err) = indexed {
+				eprintln!("Failed to index the classes of {}: {}", file_name(&jar.0), err);
+			}
+		}
+		Ok(Inventory {
+			jars: scanned.jars,
+			failed: scanned.failed,
+			classes,
+		})
+	}
+
+	/// Calls `visit` with every copy of every mod, along with its file name and the id of the mod that contains it
+	fn for_each_mod<'a>(&'a self, mut visit: impl FnMut(&'a TraversedJar, &'a str, Option<&'a str>)) {
+		for jar in &self.jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, parent| {
+				if let TraversedJar::FabricJar { .. } = nested {
+					visit(nested, file_name, parent);
+				}
+			});
+		}
+	}
+
+	fn mods(&self) -> Value {
+		let mut mods = vec![];
+		self.for_each_mod(|jar, file_name, parent| {
+			if let TraversedJar::FabricJar {
+				mod_id,
+				mod_version,
+				mod_name,
+				environment,
+				..
+			} = jar
+			{
+				mods.push(json!({
+					"id": mod_id,
+					"version": mod_version,
+					"name": mod_name,
+					"environment": environment,
+					"file": file_name,
+					"parent": parent,
+				}));
+			}
+		});
+		Value::Array(mods)
+	}
+
+	/// Every copy of a mod, with `field` selecting the part of each copy to return under `key`
+	fn mod_copies(&self, id: &str, key: &str, field: impl Fn(&TraversedJar) -> Value) -> Option<Value> {
+		let mut copies = vec![];
+		self.for_each_mod(|jar, file_name, parent| {
+			if let TraversedJar::FabricJar { mod_id, .. } = jar {
+				if mod_id == id {
+					copies.push(json!({ "file": file_name, "parent": parent, key: field(jar) }));
+				}
+			}
+		});
+		if copies.is_empty() {
+			None
+		} else {
+			Some(Value::Array(copies))
+		}
+	}
+
+	/// The jars containing a class, given either as a binary name (`a.b.C`) or a path (`a/b/C.class`)
+	fn class(&self, name: &str) -> Option<Value> {
+		let name = name.strip_suffix(".class").unwrap_or(name).replace('.', "/") + ".class";
+		self.classes.get(&name).map(|paths| json!({ "class": name, "jars": paths }))
+	}
+}
+
+fn json_response(status: u16, body: &Value) -> Response<std::io::Cursor<Vec<u8>>> {
+	let content_type = Header::from_bytes("Content-Type", "application/json").expect("header is valid");
+	Response::from_string(body.to_string())
+		.with_header(content_type)
+		.with_status_code(status)
+}
+
+fn not_found(message: String) -> (u16, Value) {
+	(404, json!({ "error": message }))
+}
+
+/// Serves the traversal results of the jars in `dir` as JSON over HTTP, until the process is stopped
+pub fn serve(address: &str, dir: &Path, scan: &ScanOptions, show_progress: bool) -> Result<()> {
+	let mut inventory = Inventory::scan(dir, scan, show_progress)?;
+	let server = Server::http(address).map_err(|err| anyhow!("Failed to listen on {}: {}", address, err))?;
+	println!("Serving {} jars on http://{}", inventory.jars.len(), address);
+
+	for request in server.incoming_requests() {
+		let url = request.url().split('?').next().unwrap_or_default().to_owned();
+		let segments: Vec<&str> = url.trim_matches('/').split('/').collect();
+		let (status, body) = match (request.method(), segments.as_slice()) {
+			(Method::Get, ["mods"]) => (200, inventory.mods()),
+			(Method::Get, ["mods", id]) => inventory
+				.mod_copies(id, "jar", |jar| json!(jar))
+				.map_or_else(|| not_found(format!("No mod with id {} found", id)), |copies| (200, copies)),
+			(Method::Get, ["mods", id, "mixins"]) => inventory
+				.mod_copies(id, "mixins", |jar| match jar {
+					TraversedJar::FabricJar { mixins, .. } => json!(mixins),
+					TraversedJar::OtherLoaderJar { .. } | TraversedJar::NonMod => Value::Null,
+				})
+				.map_or_else(|| not_found(format!("No mod with id {} found", id)), |copies| (200, copies)),
+			(Method::Get, ["classes", name]) => inventory
+				.class(name)
+				.map_or_else(|| not_found(format!("No class {} found", name)), |class| (200, class)),
+			(Method::Post, ["rescan"]) => match Inventory::scan(dir, scan, false) {
+				Ok(rescanned) => {
+					inventory = rescanned;
+					let failed: Vec<&str> = inventory.failed.iter().map(|path| file_name(path)).collect();
+					(200, json!({ "jars": inventory.jars.len(), "failed": failed }))
+				}
+				Err(err) => (500, json!({ "error": err.to_string() })),
+			},
+			_ => not_found(format!("No endpoint at {} {}", request.method(), url)),
+		};
+		if let Err(err) = request.respond(json_response(status, &body)) {
+			eprintln!("Failed to send response: {}", err);
+		}
+	}
+	Ok(())
+}
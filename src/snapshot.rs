@@ -0,0 +1,99 @@
+//! Saving the traversal results of a folder to a JSON file, so that they can be queried later without reading the jars
+//! again
+
+use crate::{display_name, JarCommand, RunContext, ScannedDir, TraversedJar};
+use anyhow::{Context, Result};
+use clap::{AppSettings, Clap};
+use serde::{Deserialize, Serialize};
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+/// Incremented whenever the format of [TraversedJar] changes in a way that older snapshots can't be read with
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+	schema_version: u32,
+	jars: Vec<SnapshotJar>,
+	/// Paths of jars that couldn't be read
+	failed: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotJar {
+	path: PathBuf,
+	jar: TraversedJar,
+}
+
+/// Only the version of a snapshot, read first so that snapshots of other versions give a clear error
+#[derive(Deserialize)]
+struct SnapshotHeader {
+	schema_version: u32,
+}
+
+/// Writes the traversal results of a folder to `path`
+pub fn write(path: &Path, jars: Vec<(PathBuf, TraversedJar)>, failed: &[PathBuf]) -> Result<()> {
+	let snapshot = Snapshot {
+		schema_version: SCHEMA_VERSION,
+		jars: jars.into_iter().map(|(path, jar)| SnapshotJar { path, jar }).collect(),
+		failed: failed.to_vec(),
+	};
+	fs::write(path, serde_json::to_string_pretty(&snapshot)?).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Reads a snapshot written by [write], failing if it was written with a different schema version
+pub fn read(path: &Path) -> Result<ScannedDir> {
+	let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+	let header: SnapshotHeader = serde_json::from_str(&contents)
+		.with_context(|| format!("{} isn't a snapshot written by raw --output", path.display()))?;
+	if header.schema_version != SCHEMA_VERSION {
+		anyhow::bail!(
+			"{} has schema version {}, but this version of mod_jar_inspector can only read version {}; create it again \
+			 with raw --output",
+			path.display(),
+			header.schema_version,
+			SCHEMA_VERSION
+		);
+	}
+	let snapshot: Snapshot = serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+	Ok(ScannedDir {
+		jars: snapshot.jars.into_iter().map(|jar| (jar.path, jar.jar)).collect(),
+		failed: snapshot.failed,
+	})
+}
+
+/// Prints raw traversal output
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct RawCommand {
+	/// Save the traversal output of every jar to this JSON file instead of printing it, so that it can be read again
+	/// with --from-snapshot
+	#[clap(long)]
+	output: Option<PathBuf>,
+}
+
+impl JarCommand for RawCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let failed_jars = context.failed_jars;
+		let quiet = context.quiet;
+		if let Some(output) = &self.output {
+			let count = processed_jars.len();
+			write(output, processed_jars, failed_jars)?;
+			if !quiet {
+				eprintln!("Saved a snapshot of {} jars to {}", count, output.display());
+			}
+		} else {
+			for jar in processed_jars {
+				println!("{} {:#?}", &display_name(&jar.0), jar.1);
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,235 @@
+//! Measuring the mods in a folder
+
+use crate::{
+	check_nesting_depth, file_name, format_size, hash_source, nested_jar_name, read_mod_json, read_nested_jar, walk_jars,
+	Environment, JarCommand, RunContext, TraversalBudget, TraversedJar,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use enum_map::{enum_map, EnumMap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs::File,
+	io::{BufReader, Cursor, Read, Seek},
+	path::PathBuf,
+};
+use zip::ZipArchive;
+
+/// Summarises the jars in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct StatsCommand {}
+
+impl JarCommand for StatsCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("stats")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let failed_jars = context.failed_jars;
+		let mut mods = 0;
+		let mut non_mods = 0;
+		let mut nested_jars = 0;
+		let mut unique_nested_jars = BTreeSet::new();
+		let mut mixins: EnumMap<Environment, usize> = enum_map! { _ => 0 };
+		let mut access_wideners = 0;
+		for jar in &processed_jars {
+			match jar.1 {
+				TraversedJar::FabricJar { .. } | TraversedJar::OtherLoaderJar { .. } => mods += 1,
+				TraversedJar::NonMod => non_mods += 1,
+			}
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, _, parent| {
+				if let TraversedJar::FabricJar {
+					hash,
+					mixins: jar_mixins,
+					access_widener_contents,
+					..
+				} = nested
+				{
+					if parent.is_some() {
+						nested_jars += 1;
+						unique_nested_jars.insert(hash);
+					}
+					for (environment, env_mixins) in jar_mixins {
+						mixins[environment] += env_mixins.len();
+					}
+					if access_widener_contents.is_some() {
+						access_wideners += 1;
+					}
+				}
+			});
+		}
+
+		let mut uncompressed_size = 0;
+		for jar in &processed_jars {
+			let mut zip = ZipArchive::new(BufReader::new(File::open(&jar.0)?))?;
+			for i in 0..zip.len() {
+				uncompressed_size += zip.by_index(i)?.size();
+			}
+		}
+
+		println!("Mods: {}", mods);
+		println!("Non-mod jars: {}", non_mods);
+		println!("Unreadable jars: {}", failed_jars.len());
+		println!("Nested jars: {} ({} unique)", nested_jars, unique_nested_jars.len());
+		println!(
+			"Mixins: {} common, {} client, {} server",
+			mixins[Environment::Both],
+			mixins[Environment::Client],
+			mixins[Environment::Server]
+		);
+		println!("Mods with access wideners: {}", access_wideners);
+		println!("Total uncompressed size: {}", format_size(uncompressed_size));
+
+		Ok(())
+	}
+}
+
+/// Ranks the jars in the current folder by size, and shows how much of it is taken up by nested jars
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct SizeCommand {}
+
+impl JarCommand for SizeCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("size")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let limits = &context.scan.limits;
+		/// Records the compressed size of every jar nested (at any depth) in the given jar (within the limits on
+		/// nested jars), returning the total size of the jars nested directly in it
+		fn nested_sizes<'a, R: Read + Seek>(
+			zip: &mut ZipArchive<R>, hash: String, jar: &'a TraversedJar, sizes: &mut Vec<(&'a TraversedJar, u64)>,
+			budget: &mut TraversalBudget,
+		) -> Result<u64> {
+			let contained_jars = match jar {
+				TraversedJar::FabricJar { contained_jars, .. } => contained_jars,
+				TraversedJar::OtherLoaderJar { .. } | TraversedJar::NonMod => return Ok(0),
+			};
+			if budget.ancestors.contains(&hash) {
+				anyhow::bail!("a nested jar contains a copy of itself");
+			}
+			let mut total = 0;
+			for jar_entry in read_mod_json(zip)?.jars {
+				let contained_jar = match contained_jars.get(&nested_jar_name(&jar_entry.file)) {
+					Some(contained_jar) => contained_jar,
+					None => continue,
+				};
+				check_nesting_depth(&jar_entry.file, budget)?;
+				let size = zip.by_name(&jar_entry.file)?.compressed_size();
+				let contents = read_nested_jar(zip, &jar_entry.file, budget)?;
+				let nested_hash = hash_source(&mut Cursor::new(&contents[..]))?;
+
+				total += size;
+				sizes.push((contained_jar, size));
+				budget.ancestors.push(hash.clone());
+				let nested = nested_sizes(
+					&mut ZipArchive::new(Cursor::new(contents))?,
+					nested_hash,
+					contained_jar,
+					sizes,
+					budget,
+				);
+				budget.ancestors.pop();
+				nested?;
+			}
+			Ok(total)
+		}
+
+		let mut rows = vec![];
+		let mut nested_copies: Vec<(&TraversedJar, u64)> = vec![];
+		for jar in &processed_jars {
+			let size = std::fs::metadata(&jar.0)?.len();
+			let mut file = BufReader::new(File::open(&jar.0)?);
+			let hash = hash_source(&mut file)?;
+			let mut zip = ZipArchive::new(file)?;
+			let mut uncompressed_size = 0;
+			for i in 0..zip.len() {
+				uncompressed_size += zip.by_index(i)?.size();
+			}
+			let nested_size = nested_sizes(&mut zip, hash, &jar.1, &mut nested_copies, &mut TraversalBudget::new(limits))?;
+			rows.push((file_name(&jar.0), size, uncompressed_size, nested_size));
+		}
+		rows.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+		let mut table = vec![[
+			"File".to_owned(),
+			"Size".to_owned(),
+			"Uncompressed".to_owned(),
+			"Nested jars".to_owned(),
+		]];
+		for (file_name, size, uncompressed_size, nested_size) in &rows {
+			let nested = if *nested_size > 0 {
+				format!("{} ({}%)", format_size(*nested_size), nested_size * 100 / size.max(&1))
+			} else {
+				String::new()
+			};
+			table.push([
+				file_name.to_string(),
+				format_size(*size),
+				format_size(*uncompressed_size),
+				nested,
+			]);
+		}
+		table.push([
+			"Total".to_owned(),
+			format_size(rows.iter().map(|row| row.1).sum()),
+			format_size(rows.iter().map(|row| row.2).sum()),
+			format_size(rows.iter().map(|row| row.3).sum()),
+		]);
+		let widths: Vec<usize> = (0..4)
+			.map(|i| table.iter().map(|row| row[i].len()).max().unwrap_or(0))
+			.collect();
+		for row in &table {
+			let line: Vec<String> = row
+				.iter()
+				.zip(&widths)
+				.map(|(cell, width)| format!("{:width$}", cell, width = width))
+				.collect();
+			println!("{}", line.join("  ").trim_end());
+		}
+
+		// A mod nested more than once, or nested while also present as a top-level jar, is only loaded once
+		let mut by_id: BTreeMap<&str, Vec<u64>> = BTreeMap::new();
+		for (jar, size) in &nested_copies {
+			if let TraversedJar::FabricJar { mod_id, .. } = jar {
+				by_id.entry(mod_id).or_default().push(*size);
+			}
+		}
+		let top_level_ids: BTreeSet<&str> = processed_jars
+			.iter()
+			.filter_map(|jar| match &jar.1 {
+				TraversedJar::FabricJar { mod_id, .. } => Some(mod_id.as_str()),
+				TraversedJar::OtherLoaderJar { .. } | TraversedJar::NonMod => None,
+			})
+			.collect();
+		let mut redundant: Vec<(&str, usize, u64, u64)> = by_id
+			.iter()
+			.filter_map(|(mod_id, sizes)| {
+				let total: u64 = sizes.iter().sum();
+				let needed = if top_level_ids.contains(mod_id) {
+					0
+				} else {
+					sizes.iter().cloned().max().unwrap_or(0)
+				};
+				(total > needed).then(|| (*mod_id, sizes.len(), total, total - needed))
+			})
+			.collect();
+		redundant.sort_by(|a, b| b.3.cmp(&a.3).then(a.0.cmp(b.0)));
+		if !redundant.is_empty() {
+			println!("Redundant nested jars:");
+			for (mod_id, copies, total, wasted) in redundant {
+				println!(
+					"    {}: {} nested copies, {} ({} redundant)",
+					mod_id,
+					copies,
+					format_size(total),
+					format_size(wasted)
+				);
+			}
+		}
+
+		Ok(())
+	}
+}
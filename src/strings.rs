@@ -0,0 +1,99 @@
+//! Searching the string constants in the classes of mods
+
+use crate::{
+	classfile, color, walk_labelled_archives, DetailLevel, JarCommand, RunContext, TraversedJar, EXIT_NO_MATCHES,
+	EXIT_UNREADABLE_JARS,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	io::Read,
+	path::PathBuf,
+	process,
+};
+
+/// Finds the classes in mods in the current folder that contain a string constant, such as a log message or URL
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct StringsCommand {
+	/// Only list string constants containing this search string
+	#[clap(long)]
+	filter: String,
+}
+
+impl JarCommand for StringsCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("strings")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		#[derive(Default)]
+		struct FoundStrings {
+			/// Chains of jar file names leading to each copy of the mod
+			paths: BTreeSet<String>,
+			/// Class names with the matching constants they contain
+			matches: BTreeSet<(String, String)>,
+		}
+
+		let filter = self.filter.to_lowercase();
+		let (walked, unreadable) = walk_labelled_archives(&processed_jars, scan, "classes", |zip| {
+			let mut matches = vec![];
+			let class_names: Vec<String> = zip
+				.file_names()
+				.filter(|name| name.ends_with(".class"))
+				.map(str::to_owned)
+				.collect();
+			for class_name in class_names {
+				let mut contents = vec![];
+				zip.by_name(&class_name)?.read_to_end(&mut contents)?;
+				let constant_pool = match classfile::ConstantPool::parse(&contents) {
+					Ok(constant_pool) => constant_pool,
+					Err(_) => continue,
+				};
+				for constant in constant_pool.strings() {
+					if constant.to_lowercase().contains(&filter) {
+						matches.push((class_name.trim_end_matches(".class").to_owned(), constant.to_owned()));
+					}
+				}
+			}
+			Ok(matches)
+		});
+
+		let mut found_strings: BTreeMap<String, FoundStrings> = BTreeMap::new();
+		for (label, path, matches) in walked {
+			// Jars without matches are left out, so that the mods listed are the ones that contain the string
+			if matches.is_empty() {
+				continue;
+			}
+			let entry = found_strings.entry(label).or_default();
+			entry.paths.insert(path);
+			entry.matches.extend(matches);
+		}
+
+		for (label, found) in &found_strings {
+			println!(
+				"{} ({})",
+				color::bold(label),
+				found.paths.iter().cloned().collect::<Vec<_>>().join(", ")
+			);
+			for (class_name, constant) in &found.matches {
+				println!("    {}: {:?}", class_name, constant);
+			}
+		}
+		if found_strings.is_empty() {
+			println!("No string constants that match the given filter found!");
+			process::exit(EXIT_NO_MATCHES);
+		}
+		if unreadable {
+			process::exit(EXIT_UNREADABLE_JARS);
+		}
+
+		Ok(())
+	}
+}
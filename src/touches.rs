@@ -0,0 +1,249 @@
+//! Finding which mods change a class
+
+use crate::{
+	classfile, color, diff, display_name, file_name, read_mixin_config, read_mod_json, walk_archives, DetailLevel, JarCommand,
+	RunContext, TraversedJar, EXIT_NO_MATCHES,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use rayon::prelude::*;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	io::Read,
+	path::PathBuf,
+	process,
+};
+
+/// Lists the mixins (with the injectors they use) and access widener entries that mods in the current folder apply to a
+/// class, to find out which mods modify it and how
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct TouchesCommand {
+	/// The class, such as net.minecraft.class_1297 or net/minecraft/class_1297, optionally followed by # and the name
+	/// of a method or field to only list changes to that member, such as net/minecraft/class_1297#method_5773
+	target: String,
+}
+
+impl JarCommand for TouchesCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("touches")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		/// Mixin and MixinExtras annotations that change the target class, as opposed to ones such as `@Shadow`
+		const INJECTORS: &[&str] = &[
+			"Inject",
+			"Redirect",
+			"ModifyArg",
+			"ModifyArgs",
+			"ModifyVariable",
+			"ModifyConstant",
+			"Overwrite",
+			"Accessor",
+			"Invoker",
+			"WrapOperation",
+			"WrapWithCondition",
+			"WrapMethod",
+			"ModifyExpressionValue",
+			"ModifyReturnValue",
+			"ModifyReceiver",
+		];
+
+		#[derive(Default)]
+		struct Touches {
+			/// Chains of jar file names leading to each copy of the mod
+			paths: BTreeSet<String>,
+			/// Mixin classes (with their side) targeting the class, with the injectors in each
+			mixins: BTreeMap<String, Vec<String>>,
+			access_widener: BTreeSet<String>,
+		}
+
+		/// The names of the members that an injector changes, from a target selector such as
+		/// `Lnet/minecraft/class_1297;method_5773()V` or the name of an accessor or invoker method
+		fn target_members(injector: &classfile::Annotation, method: &str) -> Vec<String> {
+			let without_prefix = |prefixes: &[&str]| {
+				let name = prefixes
+					.iter()
+					.find_map(|prefix| method.strip_prefix(prefix))
+					.unwrap_or(method);
+				let mut chars = name.chars();
+				chars
+					.next()
+					.map_or_else(String::new, |first| first.to_lowercase().chain(chars).collect())
+			};
+			let value = injector
+				.get("value")
+				.map(classfile::ElementValue::strings)
+				.unwrap_or_default();
+			match injector.simple_name() {
+				"Overwrite" => vec![method.to_owned()],
+				"Accessor" if value.is_empty() => vec![without_prefix(&["get", "set", "is"])],
+				"Invoker" if value.is_empty() => vec![without_prefix(&["call", "invoke"])],
+				"Accessor" | "Invoker" => value.into_iter().map(str::to_owned).collect(),
+				_ => injector
+					.get("method")
+					.map(classfile::ElementValue::strings)
+					.unwrap_or_default()
+					.into_iter()
+					.map(|selector| {
+						let selector = selector.split_once(';').map_or(selector, |(_, member)| member);
+						selector.split(['(', ':', '*']).next().unwrap_or_default().to_owned()
+					})
+					.collect(),
+			}
+		}
+
+		let (class, member) = match self.target.split_once('#') {
+			Some((class, member)) => (class.replace('.', "/"), Some(member)),
+			None => (self.target.replace('.', "/"), None),
+		};
+		let results: Vec<Result<Vec<(String, String, Touches)>>> = processed_jars
+			.par_iter()
+			.map(|jar| {
+				let mut found = vec![];
+				let mut path = vec![display_name(&jar.0)];
+				walk_archives(&jar.0, &mut path, scan, &mut |path, zip| {
+					let fabric_mod_json = match read_mod_json(zip) {
+						Ok(fabric_mod_json) => fabric_mod_json,
+						Err(_) => return Ok(()),
+					};
+					let mut touches = Touches::default();
+
+					for mixin_entry in &fabric_mod_json.mixins {
+						let config = match read_mixin_config(zip, mixin_entry.config()) {
+							Ok(config) => config,
+							Err(_) => continue,
+						};
+						let package = config.package.clone().unwrap_or_default();
+						let sides = [
+							(&config.mixins, None),
+							(&config.client, Some("client")),
+							(&config.server, Some("server")),
+						];
+						for (mixins, side) in sides {
+							for mixin in mixins {
+								let mixin_class = format!("{}.{}", package, mixin);
+								let mut contents = vec![];
+								match zip.by_name(&format!("{}.class", mixin_class.replace('.', "/"))) {
+									Ok(mut file) => file.read_to_end(&mut contents)?,
+									Err(_) => continue,
+								};
+								let class_file = match classfile::ClassFile::parse(&contents) {
+									Ok(class_file) => class_file,
+									Err(_) => continue,
+								};
+								let targets_class = class_file.annotations.iter().any(|annotation| {
+									annotation.descriptor == "Lorg/spongepowered/asm/mixin/Mixin;"
+										&& (annotation
+											.get("value")
+											.is_some_and(|value| value.classes().contains(&class.as_str()))
+											|| annotation.get("targets").is_some_and(|targets| {
+												targets.strings().iter().any(|target| target.replace('.', "/") == class)
+											}))
+								});
+								if !targets_class {
+									continue;
+								}
+
+								let mut injectors = vec![];
+								for method in &class_file.methods {
+									for annotation in &method.annotations {
+										let is_mixin = annotation.descriptor.starts_with("Lorg/spongepowered/asm/mixin/")
+											|| annotation.descriptor.starts_with("Lcom/llamalad7/mixinextras/");
+										if !is_mixin || !INJECTORS.contains(&annotation.simple_name()) {
+											continue;
+										}
+										let members = target_members(annotation, &method.name);
+										if member.is_some_and(|member| !members.iter().any(|target| target == member)) {
+											continue;
+										}
+										injectors.push(format!("@{} {}", annotation.simple_name(), members.join(", ")));
+									}
+								}
+								if member.is_some() && injectors.is_empty() {
+									continue;
+								}
+								if member.is_none() && !class_file.interfaces.is_empty() {
+									injectors.push(format!("Adds interfaces {}", class_file.interfaces.join(", ")));
+								}
+								let description = match side {
+									Some(side) => format!("{} ({})", mixin_class, side),
+									None => mixin_class,
+								};
+								touches.mixins.insert(description, injectors);
+							}
+						}
+					}
+
+					if let Some(aw_file) = &fabric_mod_json.access_widener {
+						let mut contents = String::new();
+						if let Ok(mut file) = zip.by_name(aw_file) {
+							if file.read_to_string(&mut contents).is_ok() {
+								for entry in diff::access_widener_entries(&contents) {
+									let fields: Vec<&str> = entry.split(' ').collect();
+									if fields.get(2) == Some(&class.as_str())
+										&& member.is_none_or(|member| fields.get(3) == Some(&member))
+									{
+										touches.access_widener.insert(entry);
+									}
+								}
+							}
+						}
+					}
+
+					if !touches.mixins.is_empty() || !touches.access_widener.is_empty() {
+						found.push((fabric_mod_json.id, path.join(" > "), touches));
+					}
+					Ok(())
+				})?;
+				Ok(found)
+			})
+			.collect();
+
+		let mut found_touches: BTreeMap<String, Touches> = BTreeMap::new();
+		for (jar, result) in processed_jars.iter().zip(results) {
+			match result {
+				Ok(found) => {
+					for (label, path, touches) in found {
+						let entry = found_touches.entry(label).or_default();
+						entry.paths.insert(path);
+						entry.mixins.extend(touches.mixins);
+						entry.access_widener.extend(touches.access_widener);
+					}
+				}
+				Err(err) => eprintln!("Failed to read the mixins of {}: {}", file_name(&jar.0), err),
+			}
+		}
+
+		for (label, touches) in &found_touches {
+			println!(
+				"{} ({})",
+				color::bold(label),
+				touches.paths.iter().cloned().collect::<Vec<_>>().join(", ")
+			);
+			for (mixin, injectors) in &touches.mixins {
+				println!("    Mixin {}", mixin);
+				for injector in injectors {
+					println!("        {}", injector);
+				}
+			}
+			if !touches.access_widener.is_empty() {
+				println!("    Access widener:");
+				for entry in &touches.access_widener {
+					println!("        {}", entry);
+				}
+			}
+		}
+		if found_touches.is_empty() {
+			println!("No mods that change {} found!", self.target);
+			process::exit(EXIT_NO_MATCHES);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,138 @@
+use crate::{color, display_name, verify, DetailLevel, JarCommand, JarContents, RunContext, TraversedJar};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use std::{
+	collections::BTreeMap,
+	io::{Read, Seek},
+	path::PathBuf,
+};
+use zip::ZipArchive;
+
+/// The service file that ModLauncher reads transformation services from
+const TRANSFORMATION_SERVICES: &str = "META-INF/services/cpw.mods.modlauncher.api.ITransformationService";
+
+/// The ways a Forge jar can transform classes outside of mixins
+#[derive(Default)]
+pub struct Transformers {
+	/// JavaScript coremods declared in `META-INF/coremods.json`, by name, with whether the script is in the jar
+	pub coremods: BTreeMap<String, (String, bool)>,
+	/// The `FMLCorePlugin` manifest attribute, naming a coremod class for Forge 1.12 and older
+	pub core_plugin: Option<String>,
+	/// The `TweakClass` manifest attribute, naming a LaunchWrapper tweaker
+	pub tweak_class: Option<String>,
+	/// ModLauncher transformation services
+	pub transformation_services: Vec<String>,
+	/// JavaScript files that aren't declared as coremods
+	pub scripts: Vec<String>,
+}
+
+impl Transformers {
+	pub fn is_empty(&self) -> bool {
+		self.coremods.is_empty()
+			&& self.core_plugin.is_none()
+			&& self.tweak_class.is_none()
+			&& self.transformation_services.is_empty()
+			&& self.scripts.is_empty()
+	}
+}
+
+fn read_string<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> Option<String> {
+	let mut contents = String::new();
+	zip.by_name(name).ok()?.read_to_string(&mut contents).ok()?;
+	Some(contents)
+}
+
+/// Finds the coremods, core plugins, tweakers and transformation services of a jar
+pub fn find_transformers<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<Transformers> {
+	let mut transformers = Transformers::default();
+
+	if let Some(contents) = read_string(zip, "META-INF/coremods.json") {
+		let coremods: BTreeMap<String, String> = serde_json::from_str(&contents)?;
+		for (name, path) in coremods {
+			let exists = zip.by_name(&path).is_ok();
+			transformers.coremods.insert(name, (path, exists));
+		}
+	}
+
+	if let Some(manifest) = read_string(zip, "META-INF/MANIFEST.MF") {
+		let mut attributes = verify::main_attributes(manifest.as_bytes());
+		transformers.core_plugin = attributes.remove("FMLCorePlugin");
+		transformers.tweak_class = attributes.remove("TweakClass");
+	}
+
+	if let Some(services) = read_string(zip, TRANSFORMATION_SERVICES) {
+		transformers.transformation_services = services
+			.lines()
+			.map(|line| line.split('#').next().unwrap_or_default().trim())
+			.filter(|line| !line.is_empty())
+			.map(str::to_owned)
+			.collect();
+	}
+
+	let declared: Vec<&String> = transformers.coremods.values().map(|(path, _)| path).collect();
+	transformers.scripts = zip
+		.file_names()
+		.filter(|name| name.ends_with(".js") && !declared.iter().any(|path| path.as_str() == *name))
+		.map(str::to_owned)
+		.collect();
+	transformers.scripts.sort();
+
+	Ok(transformers)
+}
+
+/// Lists the Forge coremods, core plugins, tweakers and transformation services in the current folder, which change
+/// classes without using mixins
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct TransformersCommand {}
+
+impl JarCommand for TransformersCommand {
+	fn detail_level(&self) -> DetailLevel {
+		DetailLevel::Metadata
+	}
+
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("transformers")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, context: &RunContext) -> Result<()> {
+		let scan = context.scan;
+		let mut found = false;
+		for jar in &processed_jars {
+			let found_transformers = find_transformers(&mut JarContents::open(&jar.0, scan)?.archive()?)?;
+			if found_transformers.is_empty() {
+				continue;
+			}
+
+			found = true;
+			println!("{}", color::bold(&display_name(&jar.0)));
+			for (name, (path, exists)) in &found_transformers.coremods {
+				if *exists {
+					println!("    Coremod: {} ({})", name, path);
+				} else {
+					println!(
+						"    {}",
+						color::red(&format!("Coremod: {} ({}, which isn't in the jar)", name, path))
+					);
+				}
+			}
+			if let Some(core_plugin) = &found_transformers.core_plugin {
+				println!("    Core plugin: {}", core_plugin);
+			}
+			if let Some(tweak_class) = &found_transformers.tweak_class {
+				println!("    Tweaker: {}", tweak_class);
+			}
+			for service in &found_transformers.transformation_services {
+				println!("    Transformation service: {}", service);
+			}
+			for script in &found_transformers.scripts {
+				println!("    Script: {}", script);
+			}
+		}
+		if !found {
+			println!("No coremods or class transformers found");
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,307 @@
+use crate::{
+	dependency_problems, file_name, loader_mod_ids, loader_names, walk_jars, Environment, JarCommand, RunContext, TraversedJar,
+};
+use anyhow::Result;
+use clap::{AppSettings, Clap};
+use ratatui::{
+	crossterm::event::{self, Event, KeyCode, KeyEventKind},
+	layout::{Constraint, Layout},
+	style::{Modifier, Style},
+	widgets::{Block, List, ListItem, ListState, Paragraph, Tabs},
+	DefaultTerminal, Frame,
+};
+use std::{collections::BTreeSet, path::PathBuf};
+
+const TABS: [&str; 4] = ["Jar in jar", "Mixins", "Dependencies", "Access widener"];
+
+/// A mod in the list, either a top-level jar or one nested within another mod
+struct Entry<'a> {
+	jar: &'a TraversedJar,
+	mod_id: &'a str,
+	mod_version: &'a str,
+	file_name: &'a str,
+	parent: Option<&'a str>,
+}
+
+struct App<'a> {
+	entries: Vec<Entry<'a>>,
+	problems: BTreeSet<String>,
+	/// Indices into `entries` of the mods matching the search
+	filtered: Vec<usize>,
+	list_state: ListState,
+	search: String,
+	searching: bool,
+	tab: usize,
+	scroll: u16,
+}
+
+fn jar_tree(lines: &mut Vec<String>, jar: &TraversedJar, indent: usize) {
+	if let TraversedJar::FabricJar { contained_jars, .. } = jar {
+		for (name, contained_jar) in contained_jars {
+			match contained_jar {
+				TraversedJar::FabricJar { mod_id, mod_version, .. } => {
+					lines.push(format!("{}{} {} ({})", "    ".repeat(indent), mod_id, mod_version, name))
+				}
+				TraversedJar::OtherLoaderJar { mods, .. } => lines.push(format!(
+					"{}{} ({}) [{}]",
+					"    ".repeat(indent),
+					loader_mod_ids(mods),
+					name,
+					loader_names(mods)
+				)),
+				TraversedJar::NonMod => lines.push(format!("{}{} (Not a mod)", "    ".repeat(indent), name)),
+			}
+			jar_tree(lines, contained_jar, indent + 1);
+		}
+	}
+}
+
+impl<'a> App<'a> {
+	fn new(jars: &'a [(PathBuf, TraversedJar)]) -> Self {
+		let mut entries = vec![];
+		for jar in jars {
+			walk_jars(&jar.1, file_name(&jar.0), None, &mut |nested, file_name, parent| {
+				if let TraversedJar::FabricJar { mod_id, mod_version, .. } = nested {
+					entries.push(Entry {
+						jar: nested,
+						mod_id,
+						mod_version,
+						file_name,
+						parent,
+					});
+				}
+			});
+		}
+		entries.sort_by(|a, b| a.mod_id.cmp(b.mod_id).then(a.parent.is_some().cmp(&b.parent.is_some())));
+
+		let mut app = App {
+			entries,
+			problems: dependency_problems(jars),
+			filtered: vec![],
+			list_state: ListState::default(),
+			search: String::new(),
+			searching: false,
+			tab: 0,
+			scroll: 0,
+		};
+		app.update_filter();
+		app
+	}
+
+	fn update_filter(&mut self) {
+		let search = self.search.to_lowercase();
+		self.filtered = (0..self.entries.len())
+			.filter(|&i| {
+				let entry = &self.entries[i];
+				entry.mod_id.to_lowercase().contains(&search) || entry.file_name.to_lowercase().contains(&search)
+			})
+			.collect();
+		self.list_state.select(if self.filtered.is_empty() { None } else { Some(0) });
+		self.scroll = 0;
+	}
+
+	fn select_offset(&mut self, offset: isize) {
+		if let Some(selected) = self.list_state.selected() {
+			let last = self.filtered.len() as isize - 1;
+			self.list_state
+				.select(Some((selected as isize + offset).clamp(0, last) as usize));
+			self.scroll = 0;
+		}
+	}
+
+	fn selected(&self) -> Option<&Entry<'a>> {
+		self.list_state.selected().map(|i| &self.entries[self.filtered[i]])
+	}
+
+	/// The contents of the current detail pane for the selected mod
+	fn detail_lines(&self) -> Vec<String> {
+		let entry = match self.selected() {
+			Some(entry) => entry,
+			None => return vec!["No mods match the search".to_owned()],
+		};
+		let mut lines = vec![];
+		if let TraversedJar::FabricJar {
+			mixins,
+			access_widener_contents,
+			depends,
+			recommends,
+			breaks,
+			provides,
+			..
+		} = entry.jar
+		{
+			match self.tab {
+				0 => {
+					lines.push(format!("{} {} ({})", entry.mod_id, entry.mod_version, entry.file_name));
+					if let Some(parent) = entry.parent {
+						lines.push(format!("Bundled by {}", parent));
+					}
+					lines.push(String::new());
+					jar_tree(&mut lines, entry.jar, 0);
+					if lines.len() == 3 {
+						lines.push("No nested jars".to_owned());
+					}
+				}
+				1 => {
+					for (env, env_mixins) in mixins.iter().filter(|(_, env_mixins)| !env_mixins.is_empty()) {
+						lines.push(match env {
+							Environment::Both => "Common:".to_owned(),
+							Environment::Client => "Client:".to_owned(),
+							Environment::Server => "Server:".to_owned(),
+						});
+						lines.extend(env_mixins.iter().map(|mixin| format!("    {}", mixin)));
+					}
+					if lines.is_empty() {
+						lines.push("No mixins".to_owned());
+					}
+				}
+				2 => {
+					for (kind, dependencies) in [("Depends", depends), ("Recommends", recommends), ("Breaks", breaks)] {
+						if !dependencies.is_empty() {
+							lines.push(format!("{}:", kind));
+							for (id, ranges) in dependencies {
+								lines.push(format!("    {} {}", id, ranges.join(" || ")));
+							}
+						}
+					}
+					if !provides.is_empty() {
+						lines.push(format!("Provides: {}", provides.join(", ")));
+					}
+					let prefix = format!("{} ", entry.mod_id);
+					let problems: Vec<&String> = self.problems.iter().filter(|problem| problem.starts_with(&prefix)).collect();
+					if !problems.is_empty() {
+						lines.push("Problems:".to_owned());
+						lines.extend(problems.iter().map(|problem| format!("    {}", problem)));
+					}
+					if lines.is_empty() {
+						lines.push("No dependencies".to_owned());
+					}
+				}
+				_ => match access_widener_contents {
+					Some(contents) => lines.extend(contents.lines().map(str::to_owned)),
+					None => lines.push("No access widener".to_owned()),
+				},
+			}
+		}
+		lines
+	}
+
+	fn draw(&mut self, frame: &mut Frame) {
+		let [search_area, main_area, help_area] =
+			Layout::vertical([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+		let [list_area, detail_area] =
+			Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main_area);
+		let [tabs_area, pane_area] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(detail_area);
+
+		let search_title = if self.searching {
+			"Search (Enter to finish)"
+		} else {
+			"Search (/)"
+		};
+		frame.render_widget(
+			Paragraph::new(self.search.as_str()).block(Block::bordered().title(search_title)),
+			search_area,
+		);
+
+		let items: Vec<ListItem> = self
+			.filtered
+			.iter()
+			.map(|&i| {
+				let entry = &self.entries[i];
+				match entry.parent {
+					Some(parent) => ListItem::new(format!("{} {} (in {})", entry.mod_id, entry.mod_version, parent)),
+					None => ListItem::new(format!("{} {}", entry.mod_id, entry.mod_version)),
+				}
+			})
+			.collect();
+		let list = List::new(items)
+			.block(Block::bordered().title(format!("Mods ({})", self.filtered.len())))
+			.highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+		frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+		frame.render_widget(
+			Tabs::new(TABS)
+				.select(self.tab)
+				.highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+			tabs_area,
+		);
+		frame.render_widget(
+			Paragraph::new(self.detail_lines().join("\n"))
+				.block(Block::bordered().title(TABS[self.tab]))
+				.scroll((self.scroll, 0)),
+			pane_area,
+		);
+
+		frame.render_widget(
+			Paragraph::new("q: quit  /: search  Up/Down: select mod  Tab/Left/Right: switch pane  PgUp/PgDn: scroll"),
+			help_area,
+		);
+	}
+
+	fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+		loop {
+			terminal.draw(|frame| self.draw(frame))?;
+			let key = match event::read()? {
+				Event::Key(key) if key.kind == KeyEventKind::Press => key,
+				_ => continue,
+			};
+			if self.searching {
+				match key.code {
+					KeyCode::Enter | KeyCode::Esc => self.searching = false,
+					KeyCode::Backspace => {
+						self.search.pop();
+						self.update_filter();
+					}
+					KeyCode::Char(c) => {
+						self.search.push(c);
+						self.update_filter();
+					}
+					_ => {}
+				}
+				continue;
+			}
+			match key.code {
+				KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+				KeyCode::Char('/') => self.searching = true,
+				KeyCode::Up | KeyCode::Char('k') => self.select_offset(-1),
+				KeyCode::Down | KeyCode::Char('j') => self.select_offset(1),
+				KeyCode::Home => self.select_offset(isize::MIN / 2),
+				KeyCode::End => self.select_offset(isize::MAX / 2),
+				KeyCode::Tab | KeyCode::Right => {
+					self.tab = (self.tab + 1) % TABS.len();
+					self.scroll = 0;
+				}
+				KeyCode::BackTab | KeyCode::Left => {
+					self.tab = (self.tab + TABS.len() - 1) % TABS.len();
+					self.scroll = 0;
+				}
+				KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+				KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+				_ => {}
+			}
+		}
+	}
+}
+
+/// Opens an interactive browser for the mods in `jars`, until the user quits
+pub fn run(jars: &[(PathBuf, TraversedJar)]) -> Result<()> {
+	let mut app = App::new(jars);
+	ratatui::run(|terminal| app.run(terminal))
+}
+
+/// Browses the mods in the current folder interactively, with panes for nested jars, mixins, dependencies and access wideners
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct TuiCommand {}
+
+impl JarCommand for TuiCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		None
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		run(&processed_jars)?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,355 @@
+use crate::{file_name, JarCommand, RunContext, TraversedJar};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{AppSettings, Clap};
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha384, Sha512};
+use std::{
+	collections::BTreeMap,
+	fs::File,
+	io::{BufReader, Read, Seek},
+	path::PathBuf,
+};
+use zip::ZipArchive;
+
+/// A section of a manifest or signature file, with the raw bytes it was parsed from
+struct Section<'a> {
+	raw: &'a [u8],
+	attributes: BTreeMap<String, String>,
+}
+
+/// Splits a manifest-format file into its sections, joining continuation lines
+fn parse_sections(data: &[u8]) -> Vec<Section<'_>> {
+	let mut sections = vec![];
+	let mut start = 0;
+	let mut lines: Vec<String> = vec![];
+	let mut pos = 0;
+	while pos < data.len() {
+		let end = data[pos..]
+			.iter()
+			.position(|&b| b == b'\n')
+			.map(|i| pos + i + 1)
+			.unwrap_or(data.len());
+		let line = String::from_utf8_lossy(&data[pos..end]);
+		let line = line.trim_end_matches(['\r', '\n']);
+		if line.is_empty() {
+			if !lines.is_empty() {
+				sections.push(Section {
+					raw: &data[start..end],
+					attributes: parse_attributes(&lines),
+				});
+			}
+			lines.clear();
+			start = end;
+		} else if let Some(continuation) = line.strip_prefix(' ') {
+			if let Some(last) = lines.last_mut() {
+				last.push_str(continuation);
+			}
+		} else {
+			lines.push(line.to_owned());
+		}
+		pos = end;
+	}
+	if !lines.is_empty() {
+		sections.push(Section {
+			raw: &data[start..],
+			attributes: parse_attributes(&lines),
+		});
+	}
+	sections
+}
+
+/// Reads the main attributes of a manifest, such as `Implementation-Version`
+pub fn main_attributes(manifest: &[u8]) -> BTreeMap<String, String> {
+	parse_sections(manifest)
+		.into_iter()
+		.next()
+		.map(|section| section.attributes)
+		.unwrap_or_default()
+}
+
+fn parse_attributes(lines: &[String]) -> BTreeMap<String, String> {
+	lines
+		.iter()
+		.filter_map(|line| line.split_once(": "))
+		.map(|(key, value)| (key.to_owned(), value.to_owned()))
+		.collect()
+}
+
+/// Computes the digest of `data` using the algorithm named in a `<algorithm>-Digest` attribute
+fn digest(algorithm: &str, data: &[u8]) -> Option<Vec<u8>> {
+	match algorithm.to_ascii_uppercase().as_str() {
+		"SHA1" | "SHA-1" => Some(Sha1::digest(data).to_vec()),
+		"SHA-256" => Some(Sha256::digest(data).to_vec()),
+		"SHA-384" => Some(Sha384::digest(data).to_vec()),
+		"SHA-512" => Some(Sha512::digest(data).to_vec()),
+		_ => None,
+	}
+}
+
+/// Checks the digest attributes ending in `suffix` (e.g. `-Digest`) against `data`, returning None if none are supported
+fn check_digests(attributes: &BTreeMap<String, String>, suffix: &str, data: &[u8]) -> Option<bool> {
+	let mut result = None;
+	for (key, value) in attributes {
+		if let Some(algorithm) = key.strip_suffix(suffix) {
+			if let (Some(actual), Ok(expected)) = (digest(algorithm, data), STANDARD.decode(value)) {
+				result = Some(result.unwrap_or(true) && actual == expected);
+			}
+		}
+	}
+	result
+}
+
+/// Reads a DER tag-length-value, returning the tag, contents and the remaining data
+fn read_der(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+	let tag = *data.first()?;
+	let first_length = *data.get(1)? as usize;
+	let (length, header) = if first_length & 0x80 == 0 {
+		(first_length, 2)
+	} else {
+		let count = first_length & 0x7f;
+		if count == 0 || count > 4 {
+			return None;
+		}
+		let length = data.get(2..2 + count)?.iter().fold(0, |acc, &b| (acc << 8) | b as usize);
+		(length, 2 + count)
+	};
+	let contents = data.get(header..header + length)?;
+	Some((tag, contents, &data[header + length..]))
+}
+
+/// Formats an X.501 name as a comma-separated list of its CN, OU, O and C attributes, most specific first
+fn format_name(mut name: &[u8]) -> String {
+	let mut parts = vec![];
+	while let Some((_, set, rest)) = read_der(name) {
+		let mut set = set;
+		while let Some((_, attribute, set_rest)) = read_der(set) {
+			if let Some((_, oid, value)) = read_der(attribute) {
+				let label = match oid {
+					[0x55, 0x04, 0x03] => Some("CN"),
+					[0x55, 0x04, 0x06] => Some("C"),
+					[0x55, 0x04, 0x0a] => Some("O"),
+					[0x55, 0x04, 0x0b] => Some("OU"),
+					_ => None,
+				};
+				if let (Some(label), Some((_, value, _))) = (label, read_der(value)) {
+					parts.push(format!("{}={}", label, String::from_utf8_lossy(value)));
+				}
+			}
+			set = set_rest;
+		}
+		name = rest;
+	}
+	parts.reverse();
+	parts.join(", ")
+}
+
+/// Extracts the subjects of the certificates in a PKCS#7 signature block
+fn signers(block: &[u8]) -> Option<Vec<String>> {
+	// ContentInfo { contentType, [0] SignedData }
+	let (_, content_info, _) = read_der(block)?;
+	let (_, _, rest) = read_der(content_info)?;
+	let (_, explicit, _) = read_der(rest)?;
+	// SignedData { version, digestAlgorithms, contentInfo, [0] certificates, ... }
+	let (_, signed_data, _) = read_der(explicit)?;
+	let (_, _, rest) = read_der(signed_data)?;
+	let (_, _, rest) = read_der(rest)?;
+	let (_, _, rest) = read_der(rest)?;
+	let (tag, mut certificates, _) = read_der(rest)?;
+	if tag != 0xa0 {
+		return Some(vec![]);
+	}
+
+	let mut subjects = vec![];
+	while let Some((_, certificate, rest)) = read_der(certificates) {
+		// TBSCertificate { [0] version, serialNumber, signature, issuer, validity, subject, ... }
+		let (_, mut tbs, _) = read_der(certificate)?;
+		if tbs.first() == Some(&0xa0) {
+			tbs = read_der(tbs)?.2;
+		}
+		let (_, _, tbs) = read_der(tbs)?;
+		let (_, _, tbs) = read_der(tbs)?;
+		let (_, _, tbs) = read_der(tbs)?;
+		let (_, _, tbs) = read_der(tbs)?;
+		let (_, subject, _) = read_der(tbs)?;
+		subjects.push(format_name(subject));
+		certificates = rest;
+	}
+	Some(subjects)
+}
+
+#[derive(Debug)]
+pub struct SignatureReport {
+	/// Subjects of the signing certificates, empty if the jar isn't signed
+	pub signers: Vec<String>,
+	/// Entries whose contents don't match the digest in the manifest
+	pub failed_entries: Vec<String>,
+	/// Manifest sections whose digest doesn't match the one in the signature file
+	pub failed_sections: Vec<String>,
+	/// Entries that aren't covered by the manifest
+	pub unsigned_entries: Vec<String>,
+}
+
+impl SignatureReport {
+	pub fn is_signed(&self) -> bool {
+		!self.signers.is_empty()
+	}
+
+	pub fn is_valid(&self) -> bool {
+		self.failed_entries.is_empty() && self.failed_sections.is_empty() && self.unsigned_entries.is_empty()
+	}
+}
+
+fn is_signature_file(name: &str) -> bool {
+	let upper = name.to_ascii_uppercase();
+	match upper.strip_prefix("META-INF/") {
+		Some(file) if !file.contains('/') => {
+			file == "MANIFEST.MF" || [".SF", ".RSA", ".DSA", ".EC"].iter().any(|ext| file.ends_with(ext))
+		}
+		_ => false,
+	}
+}
+
+/// Checks the signature files and per-entry digests of a jar.
+///
+/// The signer is read from the certificates in the signature block, but the cryptographic signature and the
+/// certificate chain are not validated.
+pub fn verify_jar<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<SignatureReport> {
+	let names: Vec<String> = zip.file_names().map(|s| s.to_owned()).collect();
+	let mut report = SignatureReport {
+		signers: vec![],
+		failed_entries: vec![],
+		failed_sections: vec![],
+		unsigned_entries: vec![],
+	};
+
+	let signature_files: Vec<&String> = names
+		.iter()
+		.filter(|name| is_signature_file(name) && name.to_ascii_uppercase().ends_with(".SF"))
+		.collect();
+	if signature_files.is_empty() {
+		return Ok(report);
+	}
+
+	let mut manifest = vec![];
+	zip.by_name("META-INF/MANIFEST.MF")
+		.map_err(|_| anyhow!("Jar has signature files but no manifest"))?
+		.read_to_end(&mut manifest)?;
+	let manifest_sections = parse_sections(&manifest);
+
+	for signature_file in signature_files {
+		let mut contents = vec![];
+		zip.by_name(signature_file)?.read_to_end(&mut contents)?;
+		let sections = parse_sections(&contents);
+
+		// The whole manifest digest is a shortcut; if it doesn't match, each section must match individually
+		let whole_manifest_valid = sections
+			.first()
+			.and_then(|main| check_digests(&main.attributes, "-Digest-Manifest", &manifest))
+			.unwrap_or(false);
+		if !whole_manifest_valid {
+			for section in sections.iter().skip(1) {
+				if let Some(name) = section.attributes.get("Name") {
+					let manifest_section = manifest_sections.iter().find(|s| s.attributes.get("Name") == Some(name));
+					let valid = manifest_section.and_then(|s| check_digests(&section.attributes, "-Digest", s.raw));
+					if valid != Some(true) {
+						report.failed_sections.push(name.clone());
+					}
+				}
+			}
+		}
+
+		let stem = &signature_file[..signature_file.len() - 3];
+		for extension in &["RSA", "DSA", "EC"] {
+			let block_name = format!("{}.{}", stem, extension);
+			if let Some(block_name) = names.iter().find(|name| name.eq_ignore_ascii_case(&block_name)) {
+				let mut block = vec![];
+				zip.by_name(block_name)?.read_to_end(&mut block)?;
+				report.signers.extend(signers(&block).unwrap_or_default());
+			}
+		}
+	}
+	if report.signers.is_empty() {
+		report.signers.push("unknown signer".to_owned());
+	}
+
+	for name in &names {
+		if name.ends_with('/') || is_signature_file(name) {
+			continue;
+		}
+		let section = manifest_sections
+			.iter()
+			.skip(1)
+			.find(|s| s.attributes.get("Name") == Some(name));
+		let section = match section {
+			Some(section) => section,
+			None => {
+				report.unsigned_entries.push(name.clone());
+				continue;
+			}
+		};
+
+		let mut contents = vec![];
+		zip.by_name(name)?.read_to_end(&mut contents)?;
+		if check_digests(&section.attributes, "-Digest", &contents) != Some(true) {
+			report.failed_entries.push(name.clone());
+		}
+	}
+
+	Ok(report)
+}
+
+/// Checks the signatures and entry digests of jars in the current folder
+#[derive(Clap, Debug)]
+#[clap(setting(AppSettings::UnifiedHelpMessage))]
+pub struct VerifyCommand {
+	/// List every entry that failed validation, instead of only the first few
+	#[clap(short, long)]
+	verbose: bool,
+}
+
+impl JarCommand for VerifyCommand {
+	fn rereads_jars(&self) -> Option<&'static str> {
+		Some("verify")
+	}
+
+	fn run(&self, processed_jars: Vec<(PathBuf, TraversedJar)>, _context: &RunContext) -> Result<()> {
+		fn print_entries(label: &str, entries: &[String], verbose: bool) {
+			let shown = if verbose { entries.len() } else { entries.len().min(5) };
+			for entry in &entries[..shown] {
+				println!("    {}: {}", label, entry);
+			}
+			if shown < entries.len() {
+				println!("    ... and {} more {} entries", entries.len() - shown, label.to_lowercase());
+			}
+		}
+
+		let mut signed_jars = 0;
+		let mut invalid_jars = 0;
+		for jar in &processed_jars {
+			let mut zip = ZipArchive::new(BufReader::new(File::open(&jar.0)?))?;
+			let report = verify_jar(&mut zip)?;
+			if !report.is_signed() {
+				println!("{}: not signed", file_name(&jar.0));
+				continue;
+			}
+
+			signed_jars += 1;
+			println!("{}: signed by {}", file_name(&jar.0), report.signers.join("; "));
+			if !report.is_valid() {
+				invalid_jars += 1;
+				print_entries("Modified", &report.failed_entries, self.verbose);
+				print_entries("Modified manifest section", &report.failed_sections, self.verbose);
+				print_entries("Unsigned", &report.unsigned_entries, self.verbose);
+			}
+		}
+		println!(
+			"{} of {} jars signed, {} failed validation",
+			signed_jars,
+			processed_jars.len(),
+			invalid_jars
+		);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,502 @@
+//! Fabric Loader's semantic version and version range semantics
+
+use std::{cmp::Ordering, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+	Numeric(u64),
+	Alphanumeric(String),
+}
+
+impl fmt::Display for Identifier {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Identifier::Numeric(n) => write!(f, "{}", n),
+			Identifier::Alphanumeric(s) => write!(f, "{}", s),
+		}
+	}
+}
+
+/// A semantic version as understood by Fabric Loader: any number of numeric components, an optional pre-release and
+/// optional build metadata (which is ignored when comparing)
+#[derive(Debug, Clone)]
+pub struct Version {
+	components: Vec<u64>,
+	/// None for releases; an empty list is used for range bounds that sort before every pre-release
+	pre: Option<Vec<Identifier>>,
+	build: Option<String>,
+}
+
+impl Version {
+	pub fn parse(s: &str) -> Option<Version> {
+		let (rest, build) = match s.split_once('+') {
+			Some((rest, build)) => (rest, Some(build.to_owned())),
+			None => (s, None),
+		};
+		let (core, pre) = match rest.split_once('-') {
+			Some((core, pre)) => (core, Some(pre)),
+			None => (rest, None),
+		};
+
+		let components = core.split('.').map(|c| c.parse::<u64>().ok()).collect::<Option<Vec<_>>>()?;
+		let pre = match pre {
+			Some(pre) => Some(
+				pre.split('.')
+					.map(|identifier| {
+						if identifier.is_empty() {
+							None
+						} else if let Ok(n) = identifier.parse::<u64>() {
+							Some(Identifier::Numeric(n))
+						} else {
+							Some(Identifier::Alphanumeric(identifier.to_owned()))
+						}
+					})
+					.collect::<Option<Vec<_>>>()?,
+			),
+			None => None,
+		};
+		Some(Version { components, pre, build })
+	}
+
+	fn component(&self, i: usize) -> u64 {
+		self.components.get(i).cloned().unwrap_or(0)
+	}
+
+	/// The lowest version with the given components
+	fn lowest(components: Vec<u64>) -> Version {
+		Version {
+			components,
+			pre: Some(vec![]),
+			build: None,
+		}
+	}
+}
+
+impl Ord for Version {
+	fn cmp(&self, other: &Self) -> Ordering {
+		for i in 0..self.components.len().max(other.components.len()) {
+			match self.component(i).cmp(&other.component(i)) {
+				Ordering::Equal => {}
+				ordering => return ordering,
+			}
+		}
+		match (&self.pre, &other.pre) {
+			(None, None) => Ordering::Equal,
+			(None, Some(_)) => Ordering::Greater,
+			(Some(_), None) => Ordering::Less,
+			(Some(a), Some(b)) => a.cmp(b),
+		}
+	}
+}
+
+impl PartialOrd for Version {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl PartialEq for Version {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Version {}
+
+impl fmt::Display for Version {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let components: Vec<String> = self.components.iter().map(|c| c.to_string()).collect();
+		write!(f, "{}", components.join("."))?;
+		if let Some(pre) = &self.pre {
+			if !pre.is_empty() {
+				let pre: Vec<String> = pre.iter().map(|i| i.to_string()).collect();
+				write!(f, "-{}", pre.join("."))?;
+			}
+		}
+		if let Some(build) = &self.build {
+			write!(f, "+{}", build)?;
+		}
+		Ok(())
+	}
+}
+
+/// One end of an interval, and whether it is included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bound {
+	pub version: Version,
+	pub inclusive: bool,
+}
+
+/// A contiguous set of versions, unbounded where a bound is None
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+	pub min: Option<Bound>,
+	pub max: Option<Bound>,
+}
+
+impl Interval {
+	const ANY: Interval = Interval { min: None, max: None };
+
+	fn at_least(version: Version, inclusive: bool) -> Interval {
+		Interval {
+			min: Some(Bound { version, inclusive }),
+			max: None,
+		}
+	}
+
+	fn at_most(version: Version, inclusive: bool) -> Interval {
+		Interval {
+			min: None,
+			max: Some(Bound { version, inclusive }),
+		}
+	}
+
+	fn between(min: Version, max: Version) -> Interval {
+		Interval {
+			min: Some(Bound {
+				version: min,
+				inclusive: true,
+			}),
+			max: Some(Bound {
+				version: max,
+				inclusive: false,
+			}),
+		}
+	}
+
+	fn exactly(version: Version) -> Interval {
+		Interval {
+			min: Some(Bound {
+				version: version.clone(),
+				inclusive: true,
+			}),
+			max: Some(Bound {
+				version,
+				inclusive: true,
+			}),
+		}
+	}
+
+	pub fn contains(&self, version: &Version) -> bool {
+		let above_min = match &self.min {
+			Some(min) => version > &min.version || (min.inclusive && version == &min.version),
+			None => true,
+		};
+		let below_max = match &self.max {
+			Some(max) => version < &max.version || (max.inclusive && version == &max.version),
+			None => true,
+		};
+		above_min && below_max
+	}
+
+	fn is_empty(&self) -> bool {
+		match (&self.min, &self.max) {
+			(Some(min), Some(max)) => match min.version.cmp(&max.version) {
+				Ordering::Greater => true,
+				Ordering::Equal => !(min.inclusive && max.inclusive),
+				Ordering::Less => false,
+			},
+			_ => false,
+		}
+	}
+
+	fn intersect(&self, other: &Interval) -> Option<Interval> {
+		let min = match (&self.min, &other.min) {
+			(Some(a), Some(b)) => Some(match a.version.cmp(&b.version) {
+				Ordering::Greater => a.clone(),
+				Ordering::Less => b.clone(),
+				Ordering::Equal => Bound {
+					version: a.version.clone(),
+					inclusive: a.inclusive && b.inclusive,
+				},
+			}),
+			(a, b) => a.clone().or_else(|| b.clone()),
+		};
+		let max = match (&self.max, &other.max) {
+			(Some(a), Some(b)) => Some(match a.version.cmp(&b.version) {
+				Ordering::Less => a.clone(),
+				Ordering::Greater => b.clone(),
+				Ordering::Equal => Bound {
+					version: a.version.clone(),
+					inclusive: a.inclusive && b.inclusive,
+				},
+			}),
+			(a, b) => a.clone().or_else(|| b.clone()),
+		};
+		let interval = Interval { min, max };
+		if interval.is_empty() {
+			None
+		} else {
+			Some(interval)
+		}
+	}
+}
+
+impl fmt::Display for Interval {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match (&self.min, &self.max) {
+			(None, None) => write!(f, "*"),
+			(Some(min), Some(max)) if min.version == max.version => write!(f, "={}", min.version),
+			(min, max) => {
+				let mut parts = vec![];
+				if let Some(min) = min {
+					parts.push(format!("{}{}", if min.inclusive { ">=" } else { ">" }, min.version));
+				}
+				if let Some(max) = max {
+					parts.push(format!("{}{}", if max.inclusive { "<=" } else { "<" }, max.version));
+				}
+				write!(f, "{}", parts.join(" "))
+			}
+		}
+	}
+}
+
+/// A set of versions, made up of the union of its intervals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+	pub intervals: Vec<Interval>,
+}
+
+impl VersionRange {
+	pub fn any() -> VersionRange {
+		VersionRange {
+			intervals: vec![Interval::ANY],
+		}
+	}
+
+	/// Parses a list of predicates as used in `depends`, where any of the predicates may match. Returns None if any of
+	/// the predicates can't be parsed.
+	pub fn parse_any<S: AsRef<str>>(predicates: &[S]) -> Option<VersionRange> {
+		let mut intervals = vec![];
+		for predicate in predicates {
+			intervals.extend(VersionRange::parse(predicate.as_ref())?.intervals);
+		}
+		Some(VersionRange { intervals })
+	}
+
+	/// Parses a single predicate, made up of space separated constraints that must all match
+	pub fn parse(predicate: &str) -> Option<VersionRange> {
+		let mut range = VersionRange::any();
+		for constraint in predicate.split_whitespace() {
+			range = range.intersect(&VersionRange {
+				intervals: vec![parse_constraint(constraint)?],
+			});
+		}
+		Some(range)
+	}
+
+	pub fn contains(&self, version: &Version) -> bool {
+		self.intervals.iter().any(|interval| interval.contains(version))
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.intervals.is_empty()
+	}
+
+	pub fn intersect(&self, other: &VersionRange) -> VersionRange {
+		let mut intervals = vec![];
+		for a in &self.intervals {
+			for b in &other.intervals {
+				if let Some(interval) = a.intersect(b) {
+					if !intervals.contains(&interval) {
+						intervals.push(interval);
+					}
+				}
+			}
+		}
+		VersionRange { intervals }
+	}
+
+	/// The lowest bound of the range, or None if the range has no lower bound
+	pub fn minimum(&self) -> Option<&Bound> {
+		if self.intervals.iter().any(|interval| interval.min.is_none()) {
+			return None;
+		}
+		self.intervals
+			.iter()
+			.filter_map(|interval| interval.min.as_ref())
+			.min_by(|a, b| a.version.cmp(&b.version))
+	}
+}
+
+/// Compares two version strings the way Fabric Loader does, falling back to comparing them as strings if either isn't a
+/// semantic version
+pub fn compare(a: &str, b: &str) -> Ordering {
+	match (Version::parse(a), Version::parse(b)) {
+		(Some(a), Some(b)) => a.cmp(&b),
+		_ => a.cmp(b),
+	}
+}
+
+impl fmt::Display for VersionRange {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.intervals.is_empty() {
+			return write!(f, "(no versions)");
+		}
+		let intervals: Vec<String> = self.intervals.iter().map(|i| i.to_string()).collect();
+		write!(f, "{}", intervals.join(" || "))
+	}
+}
+
+fn is_wildcard(component: &str) -> bool {
+	matches!(component, "x" | "X" | "*")
+}
+
+/// Parses the version after an operator, returning the version and the number of leading non-wildcard components
+fn parse_version_with_wildcards(s: &str) -> Option<(Version, usize)> {
+	let (core, suffix) = match s.find(['-', '+']) {
+		Some(i) => (&s[..i], &s[i..]),
+		None => (s, ""),
+	};
+	let components: Vec<&str> = core.split('.').collect();
+	let fixed = components.iter().take_while(|c| !is_wildcard(c)).count();
+	if fixed == components.len() {
+		return Some((Version::parse(s)?, fixed));
+	}
+	// Wildcards must be trailing, and can't have a pre-release or build
+	if !components[fixed..].iter().all(|c| is_wildcard(c)) || !suffix.is_empty() {
+		return None;
+	}
+	let version = Version::parse(&components[..fixed].join("."))?;
+	Some((version, fixed))
+}
+
+fn parse_constraint(constraint: &str) -> Option<Interval> {
+	if constraint == "*" {
+		return Some(Interval::ANY);
+	}
+
+	let operators = [">=", "<=", ">", "<", "=", "~", "^"];
+	let operator = operators.iter().find(|op| constraint.starts_with(*op)).cloned().unwrap_or("");
+	let version_string = &constraint[operator.len()..];
+	let wildcard = version_string.split(['-', '+']).next()?.split('.').any(is_wildcard);
+	if wildcard && version_string.split('.').next().is_some_and(is_wildcard) {
+		// x, >=x etc. match everything
+		return Some(Interval::ANY);
+	}
+	let (version, fixed) = parse_version_with_wildcards(version_string)?;
+
+	// The first version after every version matching the leading components, e.g. 1.3 for 1.2.x
+	let next_after = |components: usize| {
+		let mut next: Vec<u64> = version.components[..components].to_vec();
+		*next.last_mut().unwrap() += 1;
+		Version::lowest(next)
+	};
+	let lowest = Version::lowest(version.components.clone());
+
+	Some(match operator {
+		">=" if wildcard => Interval::at_least(lowest, true),
+		">=" => Interval::at_least(version, true),
+		"<=" if wildcard => Interval::at_most(next_after(fixed), false),
+		"<=" => Interval::at_most(version, true),
+		">" if wildcard => Interval::at_least(next_after(fixed), true),
+		">" => Interval::at_least(version, false),
+		"<" if wildcard => Interval::at_most(lowest, false),
+		"<" => Interval::at_most(version, false),
+		// ~1.2.3 allows changes to the patch version; the minor version is kept even if it is missing, so ~1 means <1.1
+		"~" if wildcard => Interval::between(lowest, next_after(fixed)),
+		"~" => {
+			let mut next = version.components.clone();
+			next.resize(2, 0);
+			next[1] += 1;
+			Interval::between(version.clone(), Version::lowest(next))
+		}
+		// ^1.2.3 allows changes to anything but the major version
+		"^" if wildcard => Interval::between(lowest, next_after(1)),
+		"^" => Interval::between(version.clone(), next_after(1)),
+		_ if wildcard => Interval::between(lowest, next_after(fixed)),
+		_ => Interval::exactly(version),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn satisfies(version: &str, predicates: &[&str]) -> bool {
+		let range = VersionRange::parse_any(predicates).unwrap_or_else(|| panic!("{:?} can't be parsed", predicates));
+		range.contains(&Version::parse(version).unwrap_or_else(|| panic!("{} can't be parsed", version)))
+	}
+
+	#[test]
+	fn at_least() {
+		assert!(satisfies("1.2.3", &[">=1.2.3"]));
+		assert!(satisfies("2.0", &[">=1.2.3"]));
+		assert!(!satisfies("1.2.2", &[">=1.2.3"]));
+		assert!(!satisfies("1.2.3-beta", &[">=1.2.3"]));
+	}
+
+	#[test]
+	fn tilde_keeps_the_minor_version() {
+		assert!(satisfies("1.0", &["~1"]));
+		assert!(satisfies("1.0.9", &["~1"]));
+		assert!(!satisfies("1.1", &["~1"]));
+		assert!(!satisfies("0.9", &["~1"]));
+
+		assert!(satisfies("1.2", &["~1.2"]));
+		assert!(satisfies("1.2.7", &["~1.2"]));
+		assert!(!satisfies("1.3", &["~1.2"]));
+
+		assert!(satisfies("1.2.5", &["~1.2.3"]));
+		assert!(!satisfies("1.2.2", &["~1.2.3"]));
+		assert!(!satisfies("1.3.0", &["~1.2.3"]));
+	}
+
+	#[test]
+	fn caret_keeps_the_major_version() {
+		assert!(satisfies("1.2.3", &["^1.2.3"]));
+		assert!(satisfies("1.9", &["^1.2.3"]));
+		assert!(!satisfies("2.0", &["^1.2.3"]));
+		assert!(!satisfies("1.2.2", &["^1.2.3"]));
+
+		// Unlike npm, Fabric Loader doesn't treat 0.x versions specially
+		assert!(satisfies("0.2.3", &["^0.2.3"]));
+		assert!(satisfies("0.3.0", &["^0.2.3"]));
+		assert!(!satisfies("1.0.0", &["^0.2.3"]));
+		assert!(!satisfies("0.2.2", &["^0.2.3"]));
+	}
+
+	#[test]
+	fn x_ranges() {
+		assert!(satisfies("1.16", &["1.16.x"]));
+		assert!(satisfies("1.16.5", &["1.16.x"]));
+		assert!(!satisfies("1.17", &["1.16.x"]));
+		assert!(!satisfies("1.15.2", &["1.16.x"]));
+
+		assert!(satisfies("1.0", &["1.x"]));
+		assert!(satisfies("1.20.4", &["1.x"]));
+		assert!(!satisfies("2.0", &["1.x"]));
+
+		for version in ["0.1", "1.16.5", "20.0.0-alpha"] {
+			assert!(satisfies(version, &["*"]));
+			assert!(satisfies(version, &["x"]));
+		}
+	}
+
+	#[test]
+	fn space_separated_constraints_must_all_match() {
+		assert!(satisfies("1.16.3", &[">=1.16.2 <1.17"]));
+		assert!(!satisfies("1.16.1", &[">=1.16.2 <1.17"]));
+		assert!(!satisfies("1.17", &[">=1.16.2 <1.17"]));
+		assert!(VersionRange::parse(">=2 <1").unwrap().is_empty());
+	}
+
+	#[test]
+	fn any_of_an_array_of_ranges_may_match() {
+		let ranges = ["1.16.x", "~1.18.2"];
+		assert!(satisfies("1.16.5", &ranges));
+		assert!(satisfies("1.18.2", &ranges));
+		assert!(!satisfies("1.17.1", &ranges));
+		assert!(!satisfies("1.19", &ranges));
+	}
+
+	#[test]
+	fn pre_releases_sort_before_releases() {
+		assert_eq!(compare("1.0.0-beta", "1.0.0"), Ordering::Less);
+		assert_eq!(compare("1.0.0-alpha", "1.0.0-beta"), Ordering::Less);
+		assert_eq!(compare("1.0.0-beta.2", "1.0.0-beta.11"), Ordering::Less);
+		assert_eq!(compare("1.0.0+build.1", "1.0.0+build.2"), Ordering::Equal);
+		assert!(satisfies("1.0.0-beta", &["<1.0.0"]));
+		assert!(!satisfies("1.1.0-beta", &["~1.0"]));
+	}
+}
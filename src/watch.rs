@@ -0,0 +1,95 @@
+use crate::is_disabled;
+use anyhow::{bail, Result};
+use notify::{RecursiveMode, Watcher};
+use std::{
+	collections::BTreeMap,
+	env,
+	ffi::{OsStr, OsString},
+	path::Path,
+	process::{Command, Stdio},
+	sync::mpsc,
+	time::Duration,
+};
+
+/// How long the folder has to stay unchanged before the command is re-run, so copying a jar only triggers one run
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Whether a filesystem event touched something that could be a jar, including disabled ones
+fn is_jar_event(event: &notify::Event) -> bool {
+	event
+		.paths
+		.iter()
+		.any(|path| path.extension() == Some(OsStr::new("jar")) || is_disabled(path))
+}
+
+/// Runs this executable again with the given arguments, returning its output
+fn run(args: &[OsString]) -> Result<String> {
+	let output = Command::new(env::current_exe()?)
+		.args(args)
+		.stderr(Stdio::inherit())
+		.output()?;
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Prints the lines that were removed from or added to the output since the last run
+fn print_delta(old: &str, new: &str) {
+	let mut counts: BTreeMap<&str, isize> = BTreeMap::new();
+	for line in old.lines() {
+		*counts.entry(line).or_default() -= 1;
+	}
+	for line in new.lines() {
+		*counts.entry(line).or_default() += 1;
+	}
+
+	let mut changed = false;
+	for line in old.lines() {
+		if let Some(count) = counts.get_mut(line).filter(|count| **count < 0) {
+			println!("- {}", line);
+			*count += 1;
+			changed = true;
+		}
+	}
+	for line in new.lines() {
+		if let Some(count) = counts.get_mut(line).filter(|count| **count > 0) {
+			println!("+ {}", line);
+			*count -= 1;
+			changed = true;
+		}
+	}
+	if !changed {
+		println!("No changes in output");
+	}
+}
+
+/// Runs the command given by `args` once, then again whenever jars in `dir` change, printing only what changed. Unless
+/// `quiet` is set, each change is announced on stderr.
+pub fn watch(dir: &Path, args: &[OsString], quiet: bool) -> Result<()> {
+	let (tx, rx) = mpsc::channel();
+	let mut watcher = notify::recommended_watcher(tx)?;
+	watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+	let mut last_output = run(args)?;
+	print!("{}", last_output);
+	if !quiet {
+		eprintln!("Watching {} for changes...", dir.display());
+	}
+
+	loop {
+		let event = match rx.recv() {
+			Ok(event) => event?,
+			Err(_) => bail!("The filesystem watcher stopped unexpectedly"),
+		};
+		if !is_jar_event(&event) {
+			continue;
+		}
+		// Wait for the folder to settle before re-running
+		while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+		let output = run(args)?;
+		if !quiet {
+			eprintln!("Jars changed, re-running...");
+		}
+		print_delta(&last_output, &output);
+		last_output = output;
+	}
+}